@@ -0,0 +1,310 @@
+use notan_egui::*;
+use serde::{Deserialize, Serialize};
+
+///Money borrowed from the bank, repaid in equal daily installments.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Loan {
+    pub amount: i32,
+    //Still owed, split so the ledger can tell repayments from interest
+    principal_left: i32,
+    interest_left: i32,
+    days_left: u32,
+}
+
+impl Loan {
+    pub fn owed(&self) -> i32 {
+        self.principal_left + self.interest_left
+    }
+
+    pub fn days_left(&self) -> u32 {
+        self.days_left
+    }
+
+    //(principal, interest) due today. The last installment pays off
+    // whatever rounding left over.
+    fn installment(&self) -> (i32, i32) {
+        let days = self.days_left.max(1) as i32;
+        (self.principal_left / days, self.interest_left / days)
+    }
+
+    pub fn daily_payment(&self) -> i32 {
+        let (principal, interest) = self.installment();
+        principal + interest
+    }
+}
+
+///Draft of a loan the player is about to take.
+pub struct LoanDraft {
+    pub amount: i32,
+    pub days: u32,
+}
+
+impl Default for LoanDraft {
+    fn default() -> Self {
+        Self {
+            amount: 5_000,
+            days: 7,
+        }
+    }
+}
+
+//What happened when the day's debts came due
+pub struct DebtService {
+    //Principal and interest paid today
+    pub principal: i32,
+    pub interest: i32,
+    //Installments that could not be paid
+    pub missed: i32,
+    pub loans_repaid: usize,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct Bank {
+    loans: Vec<Loan>,
+    //Days in a row the shop could not service its debts
+    missed_payments: u32,
+}
+
+impl Bank {
+    //Interest charged per day of a loan's term, on the amount borrowed
+    pub const DAILY_INTEREST: f32 = 0.02;
+
+    //Interest charged per day on funds below 0
+    pub const OVERDRAFT_INTEREST: f32 = 0.05;
+
+    //How far below 0 funds can go before payments start failing
+    pub const CREDIT_LINE: i32 = 5_000;
+
+    //Most the bank lends in total across all loans
+    pub const MAX_DEBT: i32 = 50_000;
+
+    pub const MAX_TERM: u32 = 30;
+
+    //Missed days in a row before the shop is declared bankrupt
+    pub const MAX_MISSED_PAYMENTS: u32 = 3;
+
+    pub fn loans(&self) -> &[Loan] {
+        &self.loans
+    }
+
+    pub fn debt(&self) -> i32 {
+        self.loans.iter().map(Loan::owed).sum()
+    }
+
+    pub fn missed_payments(&self) -> u32 {
+        self.missed_payments
+    }
+
+    pub fn is_bankrupt(&self) -> bool {
+        self.missed_payments >= Self::MAX_MISSED_PAYMENTS
+    }
+
+    pub fn interest(amount: i32, days: u32) -> i32 {
+        (amount as f32 * Self::DAILY_INTEREST * days as f32) as i32
+    }
+
+    pub fn lend(&mut self, draft: &LoanDraft) -> Result<Loan, String> {
+        if draft.amount <= 0 || draft.days == 0 || draft.days > Self::MAX_TERM {
+            return Err(format!(
+                "A loan needs an amount and a term of 1 to {} days.",
+                Self::MAX_TERM
+            ));
+        }
+
+        if self.missed_payments > 0 {
+            return Err(String::from(
+                "The bank will not lend while payments are late.",
+            ));
+        }
+
+        let interest = Self::interest(draft.amount, draft.days);
+        if self.debt() + draft.amount + interest > Self::MAX_DEBT {
+            return Err(format!(
+                "The bank lends at most ${} in total.",
+                Self::MAX_DEBT
+            ));
+        }
+
+        let loan = Loan {
+            amount: draft.amount,
+            principal_left: draft.amount,
+            interest_left: interest,
+            days_left: draft.days,
+        };
+        self.loans.push(loan.clone());
+        Ok(loan)
+    }
+
+    ///Collects the day's installments out of `funds`. An installment that
+    /// would take funds past the credit line is missed and stays owed.
+    pub fn service(&mut self, funds: i32) -> DebtService {
+        let mut service = DebtService {
+            principal: 0,
+            interest: 0,
+            missed: 0,
+            loans_repaid: 0,
+        };
+
+        let mut funds = funds;
+        for loan in self.loans.iter_mut() {
+            let (principal, interest) = loan.installment();
+
+            if funds - principal - interest < -Self::CREDIT_LINE {
+                service.missed += principal + interest;
+                continue;
+            }
+
+            funds -= principal + interest;
+            loan.principal_left -= principal;
+            loan.interest_left -= interest;
+            loan.days_left = loan.days_left.saturating_sub(1);
+            service.principal += principal;
+            service.interest += interest;
+        }
+
+        let count = self.loans.len();
+        self.loans.retain(|loan| loan.owed() > 0);
+        service.loans_repaid = count - self.loans.len();
+
+        //Being overdrawn past the credit line counts as missing a payment too
+        if service.missed > 0 || funds < -Self::CREDIT_LINE {
+            self.missed_payments += 1;
+        } else {
+            self.missed_payments = 0;
+        }
+
+        service
+    }
+
+    //True when the player presses Borrow
+    pub fn show(&self, ui: &mut Ui, draft: &mut LoanDraft, funds: i32) -> bool {
+        ui.label(format!("Funds: ${}", funds));
+        ui.label(format!("Credit Line: ${} below 0", Self::CREDIT_LINE))
+            .on_hover_text(format!(
+                "Funds below 0 are charged {:.0}% interest a day.",
+                Self::OVERDRAFT_INTEREST * 100.0
+            ));
+
+        if self.missed_payments > 0 {
+            ui.colored_label(
+                Color32::RED,
+                format!(
+                    "Missed payments: {}/{}. The shop goes bankrupt if this continues.",
+                    self.missed_payments,
+                    Self::MAX_MISSED_PAYMENTS
+                ),
+            );
+        }
+
+        ui.separator();
+
+        if self.loans.is_empty() {
+            ui.label("No loans taken.");
+        }
+
+        Grid::new("loans")
+            .num_columns(4)
+            .striped(true)
+            .show(ui, |ui| {
+                for loan in self.loans.iter() {
+                    ui.label(format!("${} borrowed", loan.amount));
+                    ui.label(format!("${} owed", loan.owed()));
+                    ui.label(format!("${}/Day", loan.daily_payment()));
+                    ui.label(format!("{} days left", loan.days_left));
+                    ui.end_row();
+                }
+            });
+
+        ui.separator();
+
+        ui.add(
+            DragValue::new(&mut draft.amount)
+                .prefix("Amount: $")
+                .speed(100)
+                .clamp_range(500..=Self::MAX_DEBT),
+        );
+        ui.add(
+            DragValue::new(&mut draft.days)
+                .prefix("Days: ")
+                .clamp_range(1..=Self::MAX_TERM),
+        );
+        ui.label(format!(
+            "Interest: ${} ({:.0}% a day)",
+            Self::interest(draft.amount, draft.days),
+            Self::DAILY_INTEREST * 100.0
+        ));
+
+        ui.button("Borrow").clicked()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draft(amount: i32, days: u32) -> LoanDraft {
+        LoanDraft { amount, days }
+    }
+
+    #[test]
+    fn lend_charges_interest_up_front() {
+        let mut bank = Bank::default();
+
+        let loan = bank.lend(&draft(1_000, 5)).unwrap();
+        assert_eq!(loan.owed(), 1_100);
+        assert_eq!(loan.daily_payment(), 220);
+        assert_eq!(bank.debt(), 1_100);
+
+        assert!(bank.lend(&draft(0, 5)).is_err());
+        assert!(bank.lend(&draft(1_000, 0)).is_err());
+        assert!(bank.lend(&draft(1_000, Bank::MAX_TERM + 1)).is_err());
+        assert!(bank.lend(&draft(Bank::MAX_DEBT, 1)).is_err());
+        assert_eq!(bank.loans().len(), 1);
+    }
+
+    #[test]
+    fn service_pays_off_loans_in_installments() {
+        let mut bank = Bank::default();
+        bank.lend(&draft(1_000, 3)).unwrap();
+
+        let mut principal = 0;
+        let mut interest = 0;
+        let mut repaid = 0;
+        for _ in 0..3 {
+            let service = bank.service(10_000);
+            assert_eq!(service.missed, 0);
+            principal += service.principal;
+            interest += service.interest;
+            repaid += service.loans_repaid;
+        }
+
+        //Rounding is paid off by the last installment
+        assert_eq!(principal, 1_000);
+        assert_eq!(interest, 60);
+        assert_eq!(repaid, 1);
+        assert_eq!(bank.debt(), 0);
+        assert_eq!(bank.missed_payments(), 0);
+    }
+
+    #[test]
+    fn missed_payments_lead_to_bankruptcy() {
+        let mut bank = Bank::default();
+        bank.lend(&draft(1_000, 5)).unwrap();
+        let broke = -Bank::CREDIT_LINE;
+
+        bank.service(broke);
+        assert_eq!(bank.missed_payments(), 1);
+        assert_eq!(bank.debt(), 1_100);
+        assert!(bank.lend(&draft(1_000, 5)).is_err());
+
+        //Paying on time again clears the count
+        bank.service(0);
+        assert_eq!(bank.missed_payments(), 0);
+
+        for _ in 0..Bank::MAX_MISSED_PAYMENTS {
+            assert!(!bank.is_bankrupt());
+            bank.service(broke);
+        }
+        assert!(bank.is_bankrupt());
+    }
+}
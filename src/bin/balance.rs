@@ -0,0 +1,219 @@
+//! Runs the shop economy headlessly and writes one CSV row per tick,
+//! so prices, costs and demand can be tuned without playing the game.
+//!
+//! Example:
+//! cargo run --bin balance -- --days 10 --difficulty medium --seed 42 \
+//!     --price 80 --price Food=30 --allocation Food=10 --allocation "Food R&D"=5 \
+//!     --output balance.csv
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use mega_hackathon_25::catalog::{Catalog, ProductId};
+use mega_hackathon_25::simulation::{Difficulty, Simulation};
+
+const USAGE: &str = "Usage: balance [--days N] [--difficulty easy|medium|hard] [--seed N]
+               [--price PRICE | --price PRODUCT=PRICE]...
+               [--allocation PRODUCT=PERCENT]... [--products FILE] [--output FILE]
+
+Prices without a product name apply to every product.
+Products are read from assets/products.json unless --products is given.
+Allocation also accepts \"Food R&D\", \"Fighter R&D\", \"Cleric R&D\"
+and \"Mage R&D\".
+Clients are sent away as soon as they have been served.
+Counter-offers from haggling clients are always rejected.";
+
+struct Options {
+    days: u32,
+    difficulty: Difficulty,
+    seed: u64,
+    prices: Vec<(Option<String>, i32)>,
+    allocation: Vec<(String, i32)>,
+    products: String,
+    output: Option<String>,
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value for {}: {}", flag, value))
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut options = Options {
+        days: 7,
+        difficulty: Difficulty::Medium,
+        seed: 0,
+        prices: Vec::new(),
+        allocation: Vec::new(),
+        products: String::from("assets/products.json"),
+        output: None,
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(flag) = args.next() {
+        if flag == "--help" || flag == "-h" {
+            println!("{}", USAGE);
+            std::process::exit(0);
+        }
+
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for {}\n\n{}", flag, USAGE))?;
+
+        match flag.as_str() {
+            "--days" => options.days = parse_number(&flag, &value)?,
+            "--seed" => options.seed = parse_number(&flag, &value)?,
+            "--difficulty" => {
+                options.difficulty = match value.to_lowercase().as_str() {
+                    "easy" => Difficulty::Easy,
+                    "medium" => Difficulty::Medium,
+                    "hard" => Difficulty::Hard,
+                    _ => return Err(format!("Unknown difficulty: {}", value)),
+                }
+            }
+            "--price" => match value.split_once('=') {
+                Some((name, price)) => options
+                    .prices
+                    .push((Some(name.to_string()), parse_number(&flag, price)?)),
+                None => options.prices.push((None, parse_number(&flag, &value)?)),
+            },
+            "--allocation" => {
+                let (name, percentage) = value
+                    .split_once('=')
+                    .ok_or_else(|| format!("Expected PRODUCT=PERCENT, got: {}", value))?;
+                options
+                    .allocation
+                    .push((name.to_string(), parse_number(&flag, percentage)?));
+            }
+            "--products" => options.products = value,
+            "--output" => options.output = Some(value),
+            _ => return Err(format!("Unknown option: {}\n\n{}", flag, USAGE)),
+        }
+    }
+
+    Ok(options)
+}
+
+fn setup(options: &Options) -> Result<Simulation, String> {
+    let data =
+        std::fs::read(&options.products).map_err(|e| format!("{}: {}", options.products, e))?;
+    let catalog = Catalog::from_json(&data).map_err(|e| format!("{}: {}", options.products, e))?;
+
+    let mut simulation =
+        Simulation::new(options.difficulty.starting_funds(), options.seed, catalog);
+    let business = simulation.business_mut();
+
+    let ids: Vec<ProductId> = business.product_ids().collect();
+    for (name, price) in options.prices.iter() {
+        match name {
+            Some(name) => {
+                let id = business
+                    .catalog()
+                    .find_by_name(name)
+                    .ok_or_else(|| format!("Unknown product: {}", name))?;
+                business.set_price(id, *price);
+            }
+            None => {
+                for &id in ids.iter() {
+                    business.set_price(id, *price);
+                }
+            }
+        }
+    }
+
+    for (name, percentage) in options.allocation.iter() {
+        let target = business
+            .find_allocation(name)
+            .ok_or_else(|| format!("Unknown allocation: {}", name))?;
+        business.set_allocation(target, *percentage)?;
+    }
+
+    Ok(simulation)
+}
+
+fn run(mut simulation: Simulation, days: u32, out: &mut dyn Write) -> io::Result<()> {
+    let ids: Vec<ProductId> = simulation.business().product_ids().collect();
+
+    write!(out, "tick,time,funds")?;
+    for &id in ids.iter() {
+        let name = simulation.business().catalog().name(id);
+        write!(out, ",{name} stock,{name} demand,{name} price,{name} sales")?;
+    }
+    writeln!(out)?;
+
+    let mut units_sold: Vec<i32> = vec![0; ids.len()];
+    let ticks = days as u64 * Simulation::TICKS_PER_DAY;
+
+    for tick in 1..=ticks {
+        simulation.step();
+
+        //Nobody is at the counter to haggle or press "Complete Order"
+        if simulation.haggle().is_some() {
+            simulation.reject_offer();
+        }
+        if simulation
+            .client()
+            .is_some_and(|client| client.is_purchase_complete() && !client.exit())
+        {
+            simulation.complete_order();
+        }
+
+        let business = simulation.business();
+        write!(
+            out,
+            "{},{:.3},{}",
+            tick,
+            simulation.elapsed(),
+            business.funds()
+        )?;
+
+        for (&id, sold) in ids.iter().zip(units_sold.iter_mut()) {
+            let total_sold = business.get_units_sold(id);
+            write!(
+                out,
+                ",{},{},{},{}",
+                business.get_quantity(id),
+                business.get_demand(id),
+                business.get_price(id),
+                total_sold - *sold
+            )?;
+            *sold = total_sold;
+        }
+        writeln!(out)?;
+
+        //The bankrupt tick still gets its row
+        if simulation.is_bankrupt() {
+            eprintln!("The shop went bankrupt on day {}.", simulation.day() + 1);
+            break;
+        }
+    }
+
+    out.flush()
+}
+
+fn try_main() -> Result<(), String> {
+    let options = parse_options()?;
+    let simulation = setup(&options)?;
+
+    let result = match &options.output {
+        Some(path) => {
+            let file = File::create(path).map_err(|e| format!("{}: {}", path, e))?;
+            run(simulation, options.days, &mut BufWriter::new(file))
+        }
+        None => run(
+            simulation,
+            options.days,
+            &mut BufWriter::new(io::stdout().lock()),
+        ),
+    };
+
+    result.map_err(|e| e.to_string())
+}
+
+fn main() {
+    if let Err(err) = try_main() {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
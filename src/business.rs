@@ -1,276 +1,403 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 
 use notan_egui::*;
 use rand::Rng;
-
-#[derive(Clone)]
-enum Quality {
+use serde::{Deserialize, Serialize};
+
+use crate::bank::{Bank, Loan, LoanDraft};
+use crate::calendar::Date;
+use crate::catalog::{Catalog, ProductId};
+use crate::events::{EventLog, GameEvent};
+use crate::expenses::{Bill, Expenses};
+use crate::ledger::{Ledger, LedgerCategory};
+use crate::marketing::{Campaign, CampaignDraft, Marketing};
+use crate::materials::{Material, Materials};
+use crate::research::{NodeId, Research, ResearchTrack};
+use crate::simulation::Simulation;
+use crate::warehouse::Warehouse;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Quality {
     Basic,
     Good,
     Exceptional,
 }
 
-#[derive(Clone)]
+impl Quality {
+    //How much the quality adds to demand, scaled by the client's quality factor
+    pub fn value(&self) -> f32 {
+        match self {
+            Quality::Basic => 0.0,
+            Quality::Good => 5.0,
+            Quality::Exceptional => 15.0,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Quality::Basic => "Basic",
+            Quality::Good => "Good",
+            Quality::Exceptional => "Exceptional",
+        }
+    }
+}
+
+//Units produced in the same run, sold oldest first
+#[derive(Clone, Serialize, Deserialize)]
+struct Batch {
+    //Seconds of game time when the batch was made
+    made: f32,
+    units: i32,
+}
+
+//Units paid for but still being made
+#[derive(Clone, Serialize, Deserialize)]
+struct ProductionOrder {
+    product: ProductId,
+    units: i32,
+    //Seconds of game time when the order was placed and when it is done
+    ordered: f32,
+    ready: f32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 struct Product {
-    quantity: i32,
+    price: i32,
+    //Percentage of funds spent on producing this product
+    allocation: i32,
+
+    //Oldest first
+    batches: VecDeque<Batch>,
     quality: Quality,
     production_cost: i32,
 
     baseline_demand: i32,
     final_demand: i32,
+
+    units_sold: i32,
 }
 
 impl Product {
     fn base(cost: i32, baseline_demand: i32) -> Self {
         Self {
-            quantity: 0,
+            price: 0,
+            allocation: 0,
+            batches: VecDeque::new(),
             quality: Quality::Basic,
             production_cost: cost,
             baseline_demand,
             final_demand: 0,
+            units_sold: 0,
         }
     }
+
+    fn quantity(&self) -> i32 {
+        self.batches.iter().map(|batch| batch.units).sum()
+    }
+}
+
+///Where a share of the funds can be allocated.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Allocation {
+    Product(ProductId),
+    Research(ResearchTrack),
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct Business {
     funds: i32,
-    prices: HashMap<String, i32>,
-    allocation: HashMap<String, i32>,
-    quantities: HashMap<String, Product>,
-    accumulated_rnd: i32,
-    accumulated_marketing: i32,
+    catalog: Catalog,
+    //Indexed by ProductId
+    products: Vec<Product>,
+    //Indexed by ResearchTrack
+    rnd_allocation: [i32; 4],
+    research: Research,
+    marketing: Marketing,
+    ledger: Ledger,
+    warehouse: Warehouse,
+    bank: Bank,
+    expenses: Expenses,
+    materials: Materials,
+    //Oldest first
+    production: VecDeque<ProductionOrder>,
+    //Seconds of game time, used to age stock
+    now: f32,
 }
 
 impl Business {
-    pub fn new(initial_fund: i32) -> Self {
-        let mut prices = HashMap::new();
-        prices.insert(String::from("Food"), 0);
+    //Research cannot bring the workshop cost of a unit below this
+    const MIN_PRODUCTION_COST: i32 = 2;
 
-        prices.insert(String::from("Fighter Armor"), 0);
-        prices.insert(String::from("Fighter Weapons"), 0);
+    //Aging goods never sell for less than this share of their price
+    const MIN_CONDITION: f32 = 0.5;
 
-        prices.insert(String::from("Cleric Armor"), 0);
-        prices.insert(String::from("Cleric Weapons"), 0);
+    //Share of a shelf life left when units count as expiring soon
+    const EXPIRY_WARNING: f32 = 0.25;
 
-        prices.insert(String::from("Mage Armor"), 0);
-        prices.insert(String::from("Mage Weapons"), 0);
+    pub fn new(initial_fund: i32, catalog: Catalog) -> Self {
+        let products = catalog
+            .ids()
+            .map(|id| {
+                let def = catalog.get(id);
+                Product::base(def.base_cost, def.baseline_demand)
+            })
+            .collect();
 
-        let mut allocation = HashMap::new();
-        allocation.insert(String::from("Food"), 0);
+        let mut ledger = Ledger::default();
+        ledger.record(LedgerCategory::Capital, None, initial_fund);
 
-        allocation.insert(String::from("Fighter Armor"), 0);
-        allocation.insert(String::from("Fighter Weapons"), 0);
+        Self {
+            funds: initial_fund,
+            catalog,
+            products,
+            rnd_allocation: [0; 4],
+            research: Research::default(),
+            marketing: Marketing::default(),
+            ledger,
+            warehouse: Warehouse::default(),
+            bank: Bank::default(),
+            expenses: Expenses::default(),
+            materials: Materials::default(),
+            production: VecDeque::new(),
+            now: 0.0,
+        }
+    }
 
-        allocation.insert(String::from("Cleric Armor"), 0);
-        allocation.insert(String::from("Cleric Weapons"), 0);
+    //price_factor: how much demand goes down the higher the price
+    //quality_factor: how much demand goes up when quality is higher
+    pub fn update_demand(&mut self, price_factor: f32, quality_factor: f32) {
+        //Allocations only spend money the shop has, never credit
+        let funds = self.funds.max(0) as f32;
+        let rnd_funds = self
+            .rnd_allocation
+            .map(|allocation| (allocation as f32 * 0.01 * funds) as i32);
+
+        for (track, spent) in ResearchTrack::ALL.into_iter().zip(rnd_funds) {
+            self.funds -= spent;
+            self.ledger.record(LedgerCategory::Research, None, -spent);
+            self.research.add_points(track, spent);
+        }
 
-        allocation.insert(String::from("Mage Armor"), 0);
-        allocation.insert(String::from("Mage Weapons"), 0);
+        let season = Date::at(self.now).season();
 
-        allocation.insert(String::from("Research & Development"), 0);
-        allocation.insert(String::from("Marketing"), 0);
+        for (id, product) in self.catalog.ids().zip(self.products.iter_mut()) {
+            let quality_value = product.quality.value();
 
-        let mut quantities = HashMap::new();
-        quantities.insert(String::from("Food"), Product::base(200, 5));
+            let baseline_demand = product.baseline_demand as f32
+                * season.demand_modifier(self.catalog.get(id).category)
+                + self.marketing.demand_boost(id, &self.catalog, self.now);
+            let price = product.price as f32;
+            product.final_demand = (baseline_demand - (price_factor * price)
+                + (quality_factor * quality_value)) as i32;
+            if product.final_demand < 0 {
+                product.final_demand = 0;
+            }
+        }
+    }
 
-        quantities.insert(String::from("Fighter Armor"), Product::base(300, 20));
-        quantities.insert(String::from("Fighter Weapons"), Product::base(275, 20));
+    pub fn catalog(&self) -> &Catalog {
+        &self.catalog
+    }
 
-        quantities.insert(String::from("Cleric Armor"), Product::base(300, 20));
-        quantities.insert(String::from("Cleric Weapons"), Product::base(275, 20));
+    pub fn product_ids(&self) -> impl Iterator<Item = ProductId> {
+        self.catalog.ids()
+    }
 
-        quantities.insert(String::from("Mage Armor"), Product::base(300, 20));
-        quantities.insert(String::from("Mage Weapons"), Product::base(275, 20));
+    pub fn get_price(&self, id: ProductId) -> i32 {
+        self.products[id.index()].price
+    }
 
-        Self {
-            funds: initial_fund,
-            prices,
-            allocation,
-            quantities,
-            accumulated_rnd: 0,
-            accumulated_marketing: 0,
-        }
+    pub fn get_demand(&self, id: ProductId) -> i32 {
+        self.products[id.index()].final_demand
     }
 
-    //price_factor: how much demand goes down the higher the price
-    //quality_factor: how much demand goes up when quality is higher
-    pub fn update_demand(&mut self, price_factor: f32, quality_factor: f32, log: &mut Vec<String>) {
-        let rnd_funds = self.allocation["Research & Development"] as f32 * 0.01 * self.funds as f32;
-        let marketing_funds = self.allocation["Marketing"] as f32 * 0.01 * self.funds as f32;
+    pub fn get_quality(&self, id: ProductId) -> Quality {
+        self.products[id.index()].quality
+    }
 
-        self.funds -= rnd_funds as i32;
-        self.funds -= marketing_funds as i32;
-        self.funds = self.funds.max(0);
+    pub fn get_quantity(&self, id: ProductId) -> i32 {
+        self.products[id.index()].quantity()
+    }
 
-        let mut rnd_demand_adder = 0;
-        let mut quality_percentage = 0;
-        let mut production_cost_deduction = 0;
+    //Total units sold over the whole run
+    pub fn get_units_sold(&self, id: ProductId) -> i32 {
+        self.products[id.index()].units_sold
+    }
 
-        self.accumulated_rnd += rnd_funds as i32;
-        self.accumulated_marketing += marketing_funds as i32;
+    //Returns the revenue made, which is limited by stock.
+    // Aging weapons and armor sell below the list price.
+    pub fn purchase(&mut self, id: ProductId, quantity_sold: i32, log: &mut EventLog) -> i32 {
+        let price = self.products[id.index()].price;
+        self.sell(
+            id,
+            quantity_sold,
+            |condition| (price as f32 * condition).round() as i32,
+            log,
+        )
+    }
 
-        let base = match self.funds {
-            0..10_000 => 20_000,
-            10_000..50_000 => 100_000,
-            _ => 200_000,
-        };
+    //Like `purchase`, but at a negotiated price per unit
+    pub fn purchase_at(
+        &mut self,
+        id: ProductId,
+        quantity_sold: i32,
+        price: i32,
+        log: &mut EventLog,
+    ) -> i32 {
+        self.sell(id, quantity_sold, |_| price, log)
+    }
 
-        let rnd_rng = rand::thread_rng().gen_range(0..100);
-        'rng: {
-            match rnd_rng {
-                0..=50 => (),
-                51..=80 => {
-                    if self.accumulated_rnd > base / 2 {
-                        self.accumulated_rnd = 0;
-                    } else {
-                        break 'rng;
-                    }
-                    log.push(String::from("Research & Development leads to a slight increase in product quality, raising demand."));
-                    rnd_demand_adder += 5;
-                    log.push(String::from(
-                        "Research also leads to a small cut in production costs.",
-                    ));
-                    production_cost_deduction = rand::thread_rng().gen_range(1..=5);
-                    quality_percentage = 10;
-                }
-                81..=90 => {
-                    if self.accumulated_rnd > base {
-                        self.accumulated_rnd = 0;
-                    } else {
-                        break 'rng;
-                    }
-                    log.push(String::from("Research & Development leads to a fair increase in product quality, raising demand."));
-                    rnd_demand_adder += 10;
-                    log.push(String::from(
-                        "Research also leads to a decent cut in production costs.",
-                    ));
-                    production_cost_deduction = rand::thread_rng().gen_range(5..=10);
-                    quality_percentage = 30;
-                }
-                91..100 => {
-                    if self.accumulated_rnd > base * 2 {
-                        self.accumulated_rnd = 0;
-                    } else {
-                        break 'rng;
-                    }
-                    log.push(String::from("Research & Development leads to an exceptional increase in product quality, raising demand greatly."));
-                    rnd_demand_adder += 50;
-                    log.push(String::from("Research also greatly cuts production costs."));
-                    production_cost_deduction = rand::thread_rng().gen_range(20..=30);
-                    quality_percentage = 100;
-                }
-                _ => (),
+    //unit_price: price of one unit given the condition of its batch
+    fn sell(
+        &mut self,
+        id: ProductId,
+        quantity_sold: i32,
+        unit_price: impl Fn(f32) -> i32,
+        log: &mut EventLog,
+    ) -> i32 {
+        let depreciation = self.catalog.get(id).depreciation;
+        let product = &mut self.products[id.index()];
+
+        let mut supply_sold = 0;
+        let mut revenue = 0;
+        while supply_sold < quantity_sold {
+            let Some(batch) = product.batches.front_mut() else {
+                break;
             };
-        }
 
-        let rnd_rng = rand::thread_rng().gen_range(0..100);
-        'rng: {
-            match rnd_rng {
-                0..=50 => (),
-                51..=80 => {
-                    if self.accumulated_marketing > base / 2 {
-                        self.accumulated_marketing = 0;
-                    } else {
-                        break 'rng;
-                    }
-                    log.push(String::from(
-                        "Marketing leads to a slight increase in consumer demand.",
-                    ));
-                    rnd_demand_adder += 5;
-                }
-                81..=90 => {
-                    if self.accumulated_marketing > base {
-                        self.accumulated_marketing = 0;
-                    } else {
-                        break 'rng;
-                    }
-                    log.push(String::from(
-                        "Marketing leads to a fair increase in consumer demand.",
-                    ));
-                    rnd_demand_adder += 20;
-                }
-                91..100 => {
-                    if self.accumulated_marketing > base * 2 {
-                        self.accumulated_marketing = 0;
-                    } else {
-                        break 'rng;
-                    }
-                    log.push(String::from(
-                        "Marketing leads to a boom in consumer demand.",
-                    ));
-                    rnd_demand_adder += 40;
-                }
-                _ => (),
-            };
+            let units = batch.units.min(quantity_sold - supply_sold);
+            let condition = Self::condition(depreciation, self.now - batch.made);
+            revenue += units * unit_price(condition);
+            supply_sold += units;
+
+            batch.units -= units;
+            if batch.units == 0 {
+                product.batches.pop_front();
+            }
         }
 
-        for (name, product) in self.quantities.iter_mut() {
-            let quality_value = match product.quality {
-                Quality::Basic => 0f32,
-                Quality::Good => 5f32,
-                Quality::Exceptional => 15f32,
+        self.funds += revenue;
+        self.ledger
+            .record(LedgerCategory::Revenue, Some(id), revenue);
+        self.expenses.collect_sales_tax(revenue);
+
+        log.push(GameEvent::SaleMade {
+            product: id,
+            units: supply_sold,
+            revenue,
+        });
+
+        product.units_sold += supply_sold;
+
+        revenue
+    }
+
+    //Share of the list price a batch is still worth after `age` seconds
+    fn condition(depreciation: f32, age: f32) -> f32 {
+        (1.0 - depreciation * age / Simulation::DAY_LENGTH).max(Self::MIN_CONDITION)
+    }
+
+    ///Throws away batches that are past their shelf life.
+    pub fn spoil(&mut self, log: &mut EventLog) {
+        let mut spoiled = Vec::new();
+
+        for (id, product) in self.catalog.ids().zip(self.products.iter_mut()) {
+            let def = self.catalog.get(id);
+            let Some(shelf_life) = def
+                .shelf_life
+                .map(|days| days * self.research.shelf_life_multiplier(ResearchTrack::of(def)))
+            else {
+                continue;
             };
 
-            product.baseline_demand += rnd_demand_adder;
-            if rand::thread_rng().gen_range(0..100) < quality_percentage {
-                match product.quality.clone() {
-                    Quality::Basic => {
-                        product.quality = Quality::Good;
-                        product.production_cost += 50;
-                    }
-                    Quality::Good => {
-                        product.quality = Quality::Exceptional;
-                        product.production_cost += 100;
-                    }
-                    _ => (),
-                }
+            let mut units = 0;
+            while product
+                .batches
+                .front()
+                .is_some_and(|batch| self.now - batch.made >= shelf_life * Simulation::DAY_LENGTH)
+            {
+                units += product.batches.pop_front().map_or(0, |batch| batch.units);
             }
-            product.production_cost -= production_cost_deduction;
-            product.production_cost = production_cost_deduction.max(quality_value as i32 + 20);
 
-            let baseline_demand = product.baseline_demand as f32;
-            let price = self.prices[name] as f32;
-            product.final_demand = (baseline_demand - (price_factor * price)
-                + (quality_factor * quality_value)) as i32;
-            if product.final_demand < 0 {
-                product.final_demand = 0;
+            if units > 0 {
+                log.push(GameEvent::Spoiled { product: id, units });
+                spoiled.push((id, units));
             }
         }
-    }
 
-    pub fn get_price(&self, name: &str) -> i32 {
-        self.prices[name]
+        //Already paid for, so the write-off only shows where the money went
+        for (id, units) in spoiled {
+            let cost = units * self.unit_cost(id);
+            self.ledger
+                .record(LedgerCategory::CostOfGoods, Some(id), cost);
+            self.ledger.record(LedgerCategory::Spoilage, Some(id), -cost);
+        }
     }
 
-    pub fn get_demand(&self, name: &str) -> i32 {
-        self.quantities[name].final_demand
-    }
+    //Units that will spoil within the last part of their shelf life
+    pub fn expiring_soon(&self, id: ProductId) -> i32 {
+        let Some(shelf_life) = self.shelf_life(id) else {
+            return 0;
+        };
 
-    pub fn get_quantity(&self, name: &str) -> i32 {
-        self.quantities[name].quantity
+        let warning = shelf_life * Simulation::DAY_LENGTH * (1.0 - Self::EXPIRY_WARNING);
+        self.products[id.index()]
+            .batches
+            .iter()
+            .filter(|batch| self.now - batch.made >= warning)
+            .map(|batch| batch.units)
+            .sum()
     }
 
-    pub fn purchase(&mut self, name: &str, quantity_sold: i32, log: &mut Vec<String>) {
-        let mut supply = self.quantities[name].quantity;
-        let mut supply_sold = quantity_sold;
-        if supply < quantity_sold {
-            supply_sold = supply;
+    fn get_allocation_percentage_mut(&mut self, target: Allocation) -> &mut i32 {
+        match target {
+            Allocation::Product(id) => &mut self.products[id.index()].allocation,
+            Allocation::Research(track) => &mut self.rnd_allocation[track as usize],
         }
+    }
 
-        supply -= supply_sold;
-        self.funds += supply_sold * self.prices[name];
+    fn total_allocation(&self) -> i32 {
+        self.products
+            .iter()
+            .map(|product| product.allocation)
+            .sum::<i32>()
+            + self.rnd_allocation.iter().sum::<i32>()
+    }
 
-        log.push(format!("+${} made!", supply_sold * self.prices[name]));
+    pub fn allocation_name(&self, target: Allocation) -> &str {
+        match target {
+            Allocation::Product(id) => self.catalog.name(id),
+            Allocation::Research(track) => track.allocation_name(),
+        }
+    }
 
-        self.quantities.get_mut(name).unwrap().quantity = supply;
+    pub fn find_allocation(&self, name: &str) -> Option<Allocation> {
+        ResearchTrack::ALL
+            .map(Allocation::Research)
+            .into_iter()
+            .find(|&target| self.allocation_name(target) == name)
+            .or_else(|| self.catalog.find_by_name(name).map(Allocation::Product))
     }
 
-    fn get_price_mut(&mut self, name: &str) -> &mut i32 {
-        self.prices.get_mut(name).unwrap()
+    pub fn set_price(&mut self, id: ProductId, price: i32) {
+        self.products[id.index()].price = price.max(0);
     }
 
-    fn get_allocation_percentage_mut(&mut self, name: &str) -> &mut i32 {
-        self.allocation.get_mut(name).unwrap()
+    pub fn set_allocation(&mut self, target: Allocation, percentage: i32) -> Result<(), String> {
+        let value = self.get_allocation_percentage_mut(target);
+        let previous = *value;
+        *value = percentage.max(0);
+
+        if self.total_allocation() > 100 {
+            *self.get_allocation_percentage_mut(target) = previous;
+            return Err(format!(
+                "Allocating {}% to [{}] would exceed 100% of funds.",
+                percentage,
+                self.allocation_name(target)
+            ));
+        }
+
+        Ok(())
     }
 
     pub fn funds(&self) -> i32 {
@@ -278,14 +405,31 @@ impl Business {
     }
 
     pub fn set_funds(&mut self, funds: i32) {
+        self.ledger
+            .record(LedgerCategory::Capital, None, funds - self.funds);
         self.funds = funds;
     }
 
-    pub fn price_label(&mut self, ui: &mut Ui, name: &str) {
+    pub fn ledger(&self) -> &Ledger {
+        &self.ledger
+    }
+
+    //Called once `day` is over, starting at 0
+    pub fn close_books(&mut self, day: u32) {
+        self.ledger.close_day(day, Simulation::DAY_LENGTH);
+    }
+
+    //Timestamp for new ledger entries and batches, in seconds of game time
+    pub fn set_time(&mut self, now: f32) {
+        self.now = now;
+        self.ledger.set_time(now);
+    }
+
+    pub fn price_label(&mut self, ui: &mut Ui, id: ProductId) {
         const MAX_PRICE: i32 = 1_000_000;
         ui.columns(2, |uis| {
-            uis[0].label(format!("{}:", name));
-            let value = self.get_price_mut(name);
+            uis[0].label(format!("{}:", self.catalog.name(id)));
+            let value = &mut self.products[id.index()].price;
             uis[1]
                 .add(DragValue::new(value).clamp_range(0..=MAX_PRICE).prefix("$"))
                 .on_hover_ui(|ui| {
@@ -295,31 +439,20 @@ impl Business {
     }
 
     pub fn show_prices(&mut self, ui: &mut Ui) {
-        self.price_label(ui, "Food");
-
-        ui.separator();
-
-        self.price_label(ui, "Fighter Armor");
-        self.price_label(ui, "Fighter Weapons");
-
-        ui.separator();
-
-        self.price_label(ui, "Cleric Armor");
-        self.price_label(ui, "Cleric Weapons");
-
-        ui.separator();
+        for id in self.catalog.ids() {
+            if self.catalog.starts_group(id) {
+                ui.separator();
+            }
 
-        self.price_label(ui, "Mage Armor");
-        self.price_label(ui, "Mage Weapons");
+            self.price_label(ui, id);
+        }
     }
 
-    pub fn allocation_label(&mut self, ui: &mut Ui, name: &str) {
-        let total_percentage_used = self
-            .allocation
-            .values()
-            .fold(0, |accumulator, &element| accumulator + element);
+    pub fn allocation_label(&mut self, ui: &mut Ui, target: Allocation) {
+        let name = self.allocation_name(target).to_string();
+        let total_percentage_used = self.total_allocation();
 
-        let value = self.get_allocation_percentage_mut(name);
+        let value = self.get_allocation_percentage_mut(target);
 
         let total_percentage_used = total_percentage_used - *value;
 
@@ -333,91 +466,586 @@ impl Business {
         .on_hover_ui(|ui| {
             ui.label("Percentage of current funds allocated.");
             ui.label(
-                "Research & Development (R&D) can result in the quality/demand increase of a product.",
+                "Research & Development (R&D) earns points for its product line, spent in the Research window.",
             );
-            ui.label("Improvements in technology may also reduce production costs.");
-            ui.label("Marketing can result in the demand increase of a product.");
+            ui.label("Research raises product quality, cuts production costs and keeps food fresh.");
         });
     }
 
-    pub fn update_quantities(&mut self) {
-        for (k, v) in self.quantities.iter_mut() {
-            let fund_percentage = self.allocation.get(k).unwrap().clone();
-            let fund_percentage = fund_percentage as f32 * 0.01;
-            let fund = (self.funds as f32 * fund_percentage) as i32;
+    ///Orders new units with the funds allocated to each product.
+    /// Space for them is reserved in the warehouse until they arrive.
+    pub fn update_quantities(&mut self, log: &mut EventLog) {
+        let mut free_space = self.warehouse.capacity() - self.used_space() - self.reserved_space();
+        let mut warehouse_full = false;
+
+        for (id, v) in self.catalog.ids().zip(self.products.iter_mut()) {
+            let fund_percentage = v.allocation as f32 * 0.01;
+            let fund = (self.funds.max(0) as f32 * fund_percentage) as i32;
+
+            //Planned at market prices, so stockpiled materials make units cheaper
+            let recipe = &self.catalog.get(id).recipe;
+            let unit_cost = v.production_cost + self.materials.recipe_cost(recipe).ceil() as i32;
+            let mut unit_production = fund / unit_cost;
+
+            //Only pay for the units that fit
+            let space = self.catalog.get(id).space;
+            if unit_production * space > free_space {
+                unit_production = free_space.max(0) / space;
+                warehouse_full = true;
+            }
+            free_space -= unit_production * space;
 
-            let unit_production = fund / v.production_cost;
+            let fund = unit_production * v.production_cost
+                + self.materials.consume(recipe, unit_production);
 
-            v.quantity += unit_production;
+            if unit_production > 0 {
+                self.production.push_back(ProductionOrder {
+                    product: id,
+                    units: unit_production,
+                    ordered: self.now,
+                    ready: self.now + self.catalog.get(id).lead_time,
+                });
+            }
             self.funds -= fund;
-            self.funds = self.funds.max(0);
+            self.ledger
+                .record(LedgerCategory::CostOfGoods, Some(id), -fund);
+        }
+
+        if warehouse_full {
+            log.push(GameEvent::WarehouseFull);
         }
     }
 
-    pub fn show_allocation(&mut self, ui: &mut Ui) {
-        ui.label(format!("Available Funds: ${}", self.funds()));
+    ///Moves finished production orders into the warehouse.
+    pub fn finish_production(&mut self) {
+        //Lead times differ, so later orders can finish first
+        let now = self.now;
+        let products = &mut self.products;
+        self.production.retain(|order| {
+            if order.ready > now {
+                return true;
+            }
 
-        self.allocation_label(ui, "Food");
+            products[order.product.index()].batches.push_back(Batch {
+                made: order.ready,
+                units: order.units,
+            });
+            false
+        });
+    }
 
-        ui.separator();
+    //Space held for units still in production
+    fn reserved_space(&self) -> i32 {
+        self.production
+            .iter()
+            .map(|order| order.units * self.catalog.get(order.product).space)
+            .sum()
+    }
 
-        self.allocation_label(ui, "Fighter Armor");
-        self.allocation_label(ui, "Fighter Weapons");
+    pub fn get_units_in_production(&self, id: ProductId) -> i32 {
+        self.production
+            .iter()
+            .filter(|order| order.product == id)
+            .map(|order| order.units)
+            .sum()
+    }
 
-        ui.separator();
+    pub fn marketing(&self) -> &Marketing {
+        &self.marketing
+    }
 
-        self.allocation_label(ui, "Cleric Armor");
-        self.allocation_label(ui, "Cleric Weapons");
+    pub fn launch_campaign(&mut self, draft: &CampaignDraft) -> Result<Campaign, String> {
+        if draft.budget <= 0 || draft.days == 0 {
+            return Err(String::from(
+                "A campaign needs a budget and at least a day.",
+            ));
+        }
 
-        ui.separator();
+        if draft.budget > self.available_funds() {
+            return Err(format!("The campaign costs ${}.", draft.budget));
+        }
 
-        self.allocation_label(ui, "Mage Armor");
-        self.allocation_label(ui, "Mage Weapons");
+        self.funds -= draft.budget;
+        self.ledger
+            .record(LedgerCategory::Marketing, None, -draft.budget);
 
-        ui.separator();
-        self.allocation_label(ui, "Research & Development");
+        let campaign = Campaign {
+            target: draft.target,
+            budget: draft.budget,
+            started: self.now,
+            duration: draft.days as f32 * Simulation::DAY_LENGTH,
+        };
+        self.marketing.launch(campaign.clone());
+        Ok(campaign)
+    }
+
+    pub fn expire_campaigns(&mut self) -> Vec<Campaign> {
+        self.marketing.expire(self.now)
+    }
+
+    pub fn research(&self) -> &Research {
+        &self.research
+    }
+
+    pub fn unlock_research(&mut self, node: NodeId) -> Result<(), String> {
+        self.research.unlock(node)?;
+        self.apply_research();
+        Ok(())
+    }
+
+    //Recomputes quality and production costs from the researched nodes
+    fn apply_research(&mut self) {
+        for (id, product) in self.catalog.ids().zip(self.products.iter_mut()) {
+            let def = self.catalog.get(id);
+            let track = ResearchTrack::of(def);
+
+            product.quality = self.research.quality(track, def.category);
+
+            let upgrade_cost = match product.quality {
+                Quality::Basic => 0,
+                Quality::Good => def.upgrade_costs.good,
+                Quality::Exceptional => def.upgrade_costs.good + def.upgrade_costs.exceptional,
+            };
+
+            product.production_cost = (def.base_cost + upgrade_cost
+                - self.research.cost_reduction(track))
+            .max(Self::MIN_PRODUCTION_COST);
+        }
+    }
+
+    //None for goods that keep forever
+    pub fn shelf_life(&self, id: ProductId) -> Option<f32> {
+        let def = self.catalog.get(id);
+        def.shelf_life
+            .map(|days| days * self.research.shelf_life_multiplier(ResearchTrack::of(def)))
+    }
+
+    pub fn used_space(&self) -> i32 {
+        self.catalog
+            .ids()
+            .map(|id| self.products[id.index()].quantity() * self.catalog.get(id).space)
+            .sum()
+    }
+
+    pub fn holding_cost(&self) -> i32 {
+        self.used_space() * Warehouse::HOLDING_COST
+    }
+
+    ///Pays a day of holding costs for everything in the warehouse.
+    pub fn pay_holding_costs(&mut self) {
+        let cost = self.holding_cost();
+        self.funds -= cost;
+        self.ledger.record(LedgerCategory::Storage, None, -cost);
+    }
+
+    //Funds plus whatever is left of the credit line
+    pub fn available_funds(&self) -> i32 {
+        self.funds + Bank::CREDIT_LINE
+    }
+
+    pub fn bank(&self) -> &Bank {
+        &self.bank
+    }
+
+    pub fn take_loan(&mut self, draft: &LoanDraft) -> Result<Loan, String> {
+        let loan = self.bank.lend(draft)?;
+        self.funds += loan.amount;
+        self.ledger.record(LedgerCategory::Loans, None, loan.amount);
+        Ok(loan)
+    }
+
+    ///Charges interest on overdrawn funds and pays the day's loan installments.
+    pub fn service_debt(&mut self, log: &mut EventLog) {
+        if self.funds < 0 {
+            let interest = (-self.funds as f32 * Bank::OVERDRAFT_INTEREST) as i32;
+            self.funds -= interest;
+            self.ledger
+                .record(LedgerCategory::Interest, None, -interest);
+        }
+
+        let service = self.bank.service(self.funds);
+        self.funds -= service.principal + service.interest;
+        self.ledger
+            .record(LedgerCategory::Loans, None, -service.principal);
+        self.ledger
+            .record(LedgerCategory::Interest, None, -service.interest);
+
+        for _ in 0..service.loans_repaid {
+            log.push(GameEvent::LoanRepaid);
+        }
+
+        if self.bank.missed_payments() > 0 {
+            log.push(GameEvent::PaymentMissed {
+                amount: service.missed,
+                strikes: self.bank.missed_payments(),
+            });
+        }
+
+        if self.bank.is_bankrupt() {
+            log.push(GameEvent::Bankrupt);
+        }
+    }
+
+    ///Cost of making one unit, with its materials at today's market prices.
+    pub fn unit_cost(&self, id: ProductId) -> i32 {
+        self.products[id.index()].production_cost
+            + self
+                .materials
+                .recipe_cost(&self.catalog.get(id).recipe)
+                .ceil() as i32
+    }
+
+    pub fn materials(&self) -> &Materials {
+        &self.materials
+    }
+
+    pub fn update_material_prices(&mut self, rng: &mut impl Rng) {
+        self.materials.update_prices(rng);
+    }
+
+    pub fn buy_material(&mut self, material: Material, units: i32) -> Result<i32, String> {
+        let cost = self.materials.cost(material, units);
+        if units <= 0 || cost > self.available_funds() {
+            return Err(format!("{} {} costs ${}.", units, material.name(), cost));
+        }
+
+        self.funds -= cost;
+        self.ledger.record(LedgerCategory::CostOfGoods, None, -cost);
+        self.materials.add(material, units);
+        Ok(cost)
+    }
+
+    //What the stock in the warehouse cost to make
+    pub fn inventory_value(&self) -> i32 {
+        self.products
+            .iter()
+            .map(|product| product.quantity() * product.production_cost)
+            .sum()
+    }
+
+    //What the next payment of `bill` would be if it were due now
+    pub fn bill_amount(&self, bill: Bill) -> i32 {
+        match bill {
+            Bill::Rent => Bill::RENT,
+            Bill::Wages => Bill::WAGES,
+            Bill::SalesTax => self.expenses.sales_tax_owed(),
+            Bill::PropertyTax => (self.inventory_value() as f32 * Bill::PROPERTY_TAX_RATE) as i32,
+        }
+    }
+
+    ///Pays every bill that falls due as `day` starts.
+    pub fn pay_bills(&mut self, day: u32, log: &mut EventLog) {
+        for bill in Bill::ALL.into_iter().filter(|bill| bill.is_due(day)) {
+            let amount = match bill {
+                Bill::SalesTax => self.expenses.settle_sales_tax(),
+                _ => self.bill_amount(bill),
+            };
+
+            if amount > 0 {
+                self.funds -= amount;
+                self.ledger.record(bill.category(), None, -amount);
+                log.push(GameEvent::BillPaid { bill, amount });
+            }
+        }
+    }
+
+    //day: current day, starting at 0
+    pub fn show_bills(&self, ui: &mut Ui, day: u32) {
+        Grid::new("bills")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                for bill in Bill::ALL {
+                    let days = bill.days_until(day);
+                    ui.label(bill.name());
+                    ui.label(format!("${}", self.bill_amount(bill)));
+                    ui.label(if days == 1 {
+                        String::from("Tomorrow")
+                    } else {
+                        format!("In {} days", days)
+                    });
+                    ui.end_row();
+                }
+            });
+    }
+
+    pub fn warehouse(&self) -> &Warehouse {
+        &self.warehouse
+    }
+
+    pub fn upgrade_warehouse(&mut self) -> Result<(), String> {
+        let cost = self.warehouse.upgrade_cost();
+        if cost > self.available_funds() {
+            return Err(format!("An upgrade costs ${}.", cost));
+        }
+
+        self.funds -= cost;
+        self.ledger.record(LedgerCategory::Upgrades, None, -cost);
+        self.warehouse.upgrade();
+        Ok(())
+    }
+
+    pub fn show_allocation(&mut self, ui: &mut Ui) {
+        ui.label(format!("Available Funds: ${}", self.funds()));
+
+        for id in self.catalog.ids() {
+            if self.catalog.starts_group(id) {
+                ui.separator();
+            }
+
+            self.allocation_label(ui, Allocation::Product(id));
+        }
 
         ui.separator();
-        self.allocation_label(ui, "Marketing");
+        for track in ResearchTrack::ALL {
+            self.allocation_label(ui, Allocation::Research(track));
+        }
     }
 
-    fn supply_label(&self, ui: &mut Ui, name: &str) {
-        let product = self.quantities[name].clone();
+    fn supply_label(&self, ui: &mut Ui, id: ProductId, icons: &HashMap<String, SizedTexture>) {
+        let product = &self.products[id.index()];
 
-        let quality = match product.quality {
-            Quality::Basic => "Basic",
-            Quality::Good => "Good",
-            Quality::Exceptional => "Exceptional",
-        };
+        let quality = product.quality.name();
 
-        let tooltip_format = format!(
-            "Quality: {}\nProduction Cost ($): {}",
-            quality, product.production_cost
+        let def = self.catalog.get(id);
+        let mut tooltip_format = format!(
+            "Quality: {}\nProduction Cost ($): {} ({} workshop + materials)",
+            quality,
+            self.unit_cost(id),
+            product.production_cost
         );
 
-        ui.label(format!(
-            "{} (Units): {}",
-            name, self.quantities[name].quantity
-        ))
-        .on_hover_text(tooltip_format);
+        if let Some(shelf_life) = self.shelf_life(id) {
+            tooltip_format += &format!("\nShelf Life (Days): {:.1}", shelf_life);
+        }
+
+        if def.depreciation > 0.0 && !product.batches.is_empty() {
+            let quantity = product.quantity() as f32;
+            let condition: f32 = product
+                .batches
+                .iter()
+                .map(|batch| {
+                    batch.units as f32 * Self::condition(def.depreciation, self.now - batch.made)
+                })
+                .sum();
+            tooltip_format += &format!("\nCondition: {:.0}%", condition / quantity * 100.0);
+        }
+
+        let expiring = self.expiring_soon(id);
+
+        ui.horizontal(|ui| {
+            if let Some(icon) = icons.get(&self.catalog.get(id).icon) {
+                ui.image(SizedTexture::new(icon.id, vec2(16.0, 16.0)));
+            }
+
+            ui.label(format!(
+                "{} (Units): {}",
+                self.catalog.name(id),
+                product.quantity()
+            ))
+            .on_hover_text(tooltip_format);
+
+            let in_production = self.get_units_in_production(id);
+            if in_production > 0 {
+                ui.weak(format!("(+{} in production)", in_production));
+            }
+
+            if expiring > 0 {
+                ui.colored_label(Color32::YELLOW, format!("({} expiring soon)", expiring));
+            }
+        });
     }
 
-    pub fn show_supply(&self, ui: &mut Ui) {
-        self.supply_label(ui, "Food");
+    pub fn show_production(&self, ui: &mut Ui) {
+        if self.production.is_empty() {
+            ui.label("Nothing is being produced. Allocate funds to a product to start.");
+            return;
+        }
 
-        ui.separator();
+        ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+            Grid::new("production_queue")
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    for order in self.production.iter() {
+                        let lead_time = order.ready - order.ordered;
+                        let progress = if lead_time > 0.0 {
+                            (self.now - order.ordered) / lead_time
+                        } else {
+                            1.0
+                        };
+
+                        ui.label(self.catalog.name(order.product));
+                        ui.label(format!("{} unit(s)", order.units));
+                        ui.add(
+                            ProgressBar::new(progress.clamp(0.0, 1.0))
+                                .desired_width(120.0)
+                                .text(format!("{:.0}s", (order.ready - self.now).max(0.0))),
+                        );
+                        ui.end_row();
+                    }
+                });
+        });
+    }
 
-        self.supply_label(ui, "Fighter Armor");
-        self.supply_label(ui, "Fighter Weapons");
+    //icons: textures keyed by the catalog's icon paths, drawn next to each product
+    pub fn show_supply(&mut self, ui: &mut Ui, icons: &HashMap<String, SizedTexture>) {
+        for id in self.catalog.ids() {
+            if self.catalog.starts_group(id) {
+                ui.separator();
+            }
+
+            self.supply_label(ui, id, icons);
+        }
 
         ui.separator();
 
-        self.supply_label(ui, "Cleric Armor");
-        self.supply_label(ui, "Cleric Weapons");
+        let used_space = self.used_space();
+        let capacity = self.warehouse.capacity();
+        ui.add(
+            ProgressBar::new(used_space as f32 / capacity as f32)
+                .text(format!("Warehouse: {}/{}", used_space, capacity)),
+        )
+        .on_hover_text(format!("Holding Costs ($/Day): {}", self.holding_cost()));
+
+        let cost = self.warehouse.upgrade_cost();
+        if ui
+            .add_enabled(
+                cost <= self.available_funds(),
+                Button::new(format!(
+                    "Expand (+{} Space) ${}",
+                    Warehouse::UPGRADE_CAPACITY,
+                    cost
+                )),
+            )
+            .clicked()
+        {
+            let _ = self.upgrade_warehouse();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        ui.separator();
+    const DAY: f32 = Simulation::DAY_LENGTH;
+
+    fn business() -> Business {
+        Business::new(10_000, Catalog::default())
+    }
+
+    fn id(business: &Business, name: &str) -> ProductId {
+        business.catalog().find_by_name(name).unwrap()
+    }
+
+    fn stock(business: &mut Business, id: ProductId, units: i32, made: f32) {
+        business.products[id.index()]
+            .batches
+            .push_back(Batch { made, units });
+    }
+
+    fn logged(log: &EventLog, matches: impl Fn(&GameEvent) -> bool) -> bool {
+        log.iter().any(|logged| matches(&logged.event))
+    }
+
+    #[test]
+    fn spoiled_stock_is_written_off() {
+        let mut business = business();
+        let mut log = EventLog::default();
+        let food = id(&business, "Food");
+        let shelf_life = business.shelf_life(food).unwrap();
+        stock(&mut business, food, 3, 0.0);
+        stock(&mut business, food, 4, DAY);
+
+        business.set_time(shelf_life * DAY);
+        business.spoil(&mut log);
+
+        assert_eq!(business.get_quantity(food), 4);
+        assert!(logged(&log, |event| matches!(
+            event,
+            GameEvent::Spoiled { units: 3, .. }
+        )));
+
+        let statement = business.ledger().statement();
+        assert_eq!(statement.spoilage, 3 * business.unit_cost(food));
+        assert_eq!(statement.net_profit(), 0);
+        assert_eq!(business.funds(), 10_000);
+    }
+
+    #[test]
+    fn aging_stock_sells_for_less() {
+        let mut business = business();
+        let mut log = EventLog::default();
+        let armor = id(&business, "Fighter Armor");
+        let depreciation = business.catalog().get(armor).depreciation;
+        business.products[armor.index()].price = 100;
+
+        stock(&mut business, armor, 1, 0.0);
+        business.set_time(10.0 * DAY);
+        let expected = (100.0 * (1.0 - depreciation * 10.0)).round() as i32;
+        assert_eq!(business.purchase(armor, 1, &mut log), expected);
+
+        //Never below MIN_CONDITION of the price
+        stock(&mut business, armor, 1, 0.0);
+        business.set_time(1_000.0 * DAY);
+        assert_eq!(business.purchase(armor, 1, &mut log), 50);
+    }
+
+    #[test]
+    fn production_stops_at_warehouse_capacity() {
+        let mut business = Business::new(1_000_000, Catalog::default());
+        let mut log = EventLog::default();
+        let armor = id(&business, "Fighter Armor");
+        let space = business.catalog().get(armor).space;
+        let capacity = business.warehouse().capacity();
+        business
+            .set_allocation(Allocation::Product(armor), 100)
+            .unwrap();
+
+        business.update_quantities(&mut log);
+
+        let ordered = business.get_units_in_production(armor);
+        assert_eq!(ordered, capacity / space);
+        assert!(logged(&log, |event| matches!(
+            event,
+            GameEvent::WarehouseFull
+        )));
+
+        //Units still in production keep their space reserved
+        let funds = business.funds();
+        business.update_quantities(&mut log);
+        assert_eq!(business.get_units_in_production(armor), ordered);
+        assert_eq!(business.funds(), funds);
+    }
 
-        self.supply_label(ui, "Mage Armor");
-        self.supply_label(ui, "Mage Weapons");
+    #[test]
+    fn orders_arrive_after_their_lead_time() {
+        let mut business = business();
+        let mut log = EventLog::default();
+        let food = id(&business, "Food");
+        let armor = id(&business, "Fighter Armor");
+        business
+            .set_allocation(Allocation::Product(food), 10)
+            .unwrap();
+        business
+            .set_allocation(Allocation::Product(armor), 10)
+            .unwrap();
+
+        business.update_quantities(&mut log);
+        let food_units = business.get_units_in_production(food);
+        let armor_units = business.get_units_in_production(armor);
+        assert!(food_units > 0 && armor_units > 0);
+
+        business.set_time(business.catalog().get(food).lead_time);
+        business.finish_production();
+        assert_eq!(business.get_quantity(food), food_units);
+        assert_eq!(business.get_units_in_production(food), 0);
+        assert_eq!(business.get_quantity(armor), 0);
+
+        business.set_time(business.catalog().get(armor).lead_time);
+        business.finish_production();
+        assert_eq!(business.get_quantity(armor), armor_units);
+        assert_eq!(business.get_units_in_production(armor), 0);
     }
 }
@@ -0,0 +1,171 @@
+use serde::{Deserialize, Serialize};
+
+use crate::client::Prioritization;
+use crate::simulation::Simulation;
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    pub const ALL: [Weekday; 7] = [
+        Weekday::Monday,
+        Weekday::Tuesday,
+        Weekday::Wednesday,
+        Weekday::Thursday,
+        Weekday::Friday,
+        Weekday::Saturday,
+        Weekday::Sunday,
+    ];
+
+    pub fn name(&self) -> &str {
+        match self {
+            Weekday::Monday => "Monday",
+            Weekday::Tuesday => "Tuesday",
+            Weekday::Wednesday => "Wednesday",
+            Weekday::Thursday => "Thursday",
+            Weekday::Friday => "Friday",
+            Weekday::Saturday => "Saturday",
+            Weekday::Sunday => "Sunday",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Season {
+    Spring,
+    Summer,
+    //Adventurers head into the dungeons, so gear sells best just before
+    DungeonSeason,
+    Winter,
+}
+
+impl Season {
+    pub const ALL: [Season; 4] = [
+        Season::Spring,
+        Season::Summer,
+        Season::DungeonSeason,
+        Season::Winter,
+    ];
+
+    pub fn name(&self) -> &str {
+        match self {
+            Season::Spring => "Spring",
+            Season::Summer => "Summer",
+            Season::DungeonSeason => "Dungeon Season",
+            Season::Winter => "Winter",
+        }
+    }
+
+    //Multiplier on the baseline demand of every product in `category`
+    pub fn demand_modifier(&self, category: Prioritization) -> f32 {
+        match (self, category) {
+            (Season::Summer, Prioritization::Armor) => 1.5,
+            (Season::Summer, Prioritization::Weapon) => 1.25,
+            (Season::DungeonSeason, Prioritization::Weapon) => 1.25,
+            (Season::DungeonSeason, Prioritization::Food) => 1.25,
+            (Season::Winter, Prioritization::Food) => 1.5,
+            (Season::Winter, Prioritization::Armor) => 0.75,
+            (Season::Winter, Prioritization::Weapon) => 0.75,
+            _ => 1.0,
+        }
+    }
+}
+
+///A day in the game, counted from the start of the run.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Date {
+    //Starting at 0
+    day: u32,
+}
+
+impl Date {
+    pub const DAYS_PER_WEEK: u32 = 7;
+    pub const WEEKS_PER_SEASON: u32 = 2;
+    pub const DAYS_PER_SEASON: u32 = Self::DAYS_PER_WEEK * Self::WEEKS_PER_SEASON;
+
+    pub fn new(day: u32) -> Self {
+        Self { day }
+    }
+
+    //Date of the day `time` seconds of game time fall in
+    pub fn at(time: f32) -> Self {
+        Self::new((time / Simulation::DAY_LENGTH) as u32)
+    }
+
+    pub fn day(&self) -> u32 {
+        self.day
+    }
+
+    pub fn weekday(&self) -> Weekday {
+        Weekday::ALL[(self.day % Self::DAYS_PER_WEEK) as usize]
+    }
+
+    pub fn season(&self) -> Season {
+        Season::ALL[(self.day / Self::DAYS_PER_SEASON) as usize % Season::ALL.len()]
+    }
+
+    //Starting at 1
+    pub fn year(&self) -> u32 {
+        self.day / (Self::DAYS_PER_SEASON * Season::ALL.len() as u32) + 1
+    }
+
+    //Starting at 1
+    pub fn week_of_season(&self) -> u32 {
+        self.day % Self::DAYS_PER_SEASON / Self::DAYS_PER_WEEK + 1
+    }
+
+    pub fn name(&self) -> String {
+        format!(
+            "Day {}, {}, Week {} of {}, Year {}",
+            self.day + 1,
+            self.weekday().name(),
+            self.week_of_season(),
+            self.season().name(),
+            self.year()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_map_to_weekdays_and_seasons() {
+        assert!(Date::new(0).weekday() == Weekday::Monday);
+        assert!(Date::new(6).weekday() == Weekday::Sunday);
+        assert!(Date::new(7).weekday() == Weekday::Monday);
+
+        assert!(Date::new(13).season() == Season::Spring);
+        assert!(Date::new(14).season() == Season::Summer);
+        assert!(Date::new(28).season() == Season::DungeonSeason);
+        assert!(Date::new(55).season() == Season::Winter);
+        assert!(Date::new(56).season() == Season::Spring);
+
+        assert_eq!(Date::new(8).week_of_season(), 2);
+        assert_eq!(Date::new(55).year(), 1);
+        assert_eq!(Date::new(56).year(), 2);
+        assert_eq!(
+            Date::new(15).name(),
+            "Day 16, Tuesday, Week 1 of Summer, Year 1"
+        );
+    }
+
+    #[test]
+    fn time_falls_in_the_day_it_started() {
+        let day = Simulation::DAY_LENGTH;
+
+        assert_eq!(Date::at(0.0).day(), 0);
+        assert_eq!(Date::at(day - 0.5).day(), 0);
+        assert_eq!(Date::at(day).day(), 1);
+        assert_eq!(Date::at(3.5 * day).day(), 3);
+    }
+}
@@ -0,0 +1,254 @@
+use serde::{Deserialize, Serialize};
+
+use crate::client::{ClassType, Prioritization};
+use crate::materials::Recipe;
+
+///Index of a product in the catalog.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+pub struct ProductId(usize);
+
+impl ProductId {
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+//Extra production cost per unit once R&D raises the quality
+#[derive(Clone, Serialize, Deserialize)]
+pub struct UpgradeCosts {
+    pub good: i32,
+    pub exceptional: i32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ProductDef {
+    pub name: String,
+    pub category: Prioritization,
+    //None for goods every class buys, like food
+    pub class: Option<ClassType>,
+    //Workshop cost per unit, paid on top of the materials in the recipe
+    pub base_cost: i32,
+    //Materials that go into one unit
+    #[serde(default)]
+    pub recipe: Recipe,
+    pub baseline_demand: i32,
+    //Warehouse space taken up by one unit
+    #[serde(default = "ProductDef::default_space")]
+    pub space: i32,
+    //Seconds between ordering a unit and it reaching the warehouse
+    #[serde(default)]
+    pub lead_time: f32,
+    pub upgrade_costs: UpgradeCosts,
+    //Days a batch keeps before spoiling; None for goods that keep forever
+    #[serde(default)]
+    pub shelf_life: Option<f32>,
+    //Share of the price a batch loses for each day it sits in stock
+    #[serde(default)]
+    pub depreciation: f32,
+    //Relative to the assets folder
+    pub icon: String,
+}
+
+///Every product the shop can sell, in the order the UI shows them.
+/// Loaded from `assets/products.json` so goods can be added or
+/// rebalanced without recompiling.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Catalog {
+    products: Vec<ProductDef>,
+}
+
+impl ProductDef {
+    //Cost of one Basic unit with materials at their usual prices
+    pub fn standard_cost(&self) -> i32 {
+        self.base_cost
+            + self
+                .recipe
+                .iter()
+                .map(|(material, units)| (material.base_price() * *units as f32) as i32)
+                .sum::<i32>()
+    }
+
+    fn default_space() -> i32 {
+        1
+    }
+}
+
+//Used when the game cannot load the file at runtime
+impl Default for Catalog {
+    fn default() -> Self {
+        Self::from_json(include_bytes!("../assets/products.json"))
+            .expect("bundled products.json is invalid")
+    }
+}
+
+impl Catalog {
+    pub const PATH: &'static str = "../assets/products.json";
+
+    pub fn from_json(data: &[u8]) -> Result<Self, String> {
+        let catalog: Catalog = serde_json::from_slice(data).map_err(|e| e.to_string())?;
+        catalog.validate()?;
+        Ok(catalog)
+    }
+
+    ///Checks the products make sense, whether they came from
+    /// products.json or from a save.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.is_empty() {
+            return Err(String::from("The catalog has no products."));
+        }
+
+        for product in self.products.iter() {
+            if product.base_cost <= 0 {
+                return Err(format!(
+                    "[{}] must cost more than $0 to produce.",
+                    product.name
+                ));
+            }
+
+            if product.recipe.values().any(|&units| units <= 0) {
+                return Err(format!(
+                    "[{}] must use at least one unit of every material in its recipe.",
+                    product.name
+                ));
+            }
+
+            if product.space <= 0 {
+                return Err(format!(
+                    "[{}] must take up some warehouse space.",
+                    product.name
+                ));
+            }
+
+            if product.lead_time < 0.0 {
+                return Err(format!(
+                    "[{}] cannot take negative time to produce.",
+                    product.name
+                ));
+            }
+
+            if product.shelf_life.is_some_and(|days| days <= 0.0) {
+                return Err(format!(
+                    "[{}] must keep for more than 0 days.",
+                    product.name
+                ));
+            }
+
+            if !(0.0..1.0).contains(&product.depreciation) {
+                return Err(format!(
+                    "[{}] must lose between 0% and 100% of its value per day.",
+                    product.name
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = ProductId> {
+        (0..self.products.len()).map(ProductId)
+    }
+
+    pub fn len(&self) -> usize {
+        self.products.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.products.is_empty()
+    }
+
+    pub fn get(&self, id: ProductId) -> &ProductDef {
+        &self.products[id.0]
+    }
+
+    pub fn name(&self, id: ProductId) -> &str {
+        &self.products[id.0].name
+    }
+
+    pub fn find_by_name(&self, name: &str) -> Option<ProductId> {
+        self.products
+            .iter()
+            .position(|product| product.name == name)
+            .map(ProductId)
+    }
+
+    //Class-less products (food) match clients of any class
+    pub fn find(&self, category: &Prioritization, class: &ClassType) -> Option<ProductId> {
+        self.products
+            .iter()
+            .position(|product| {
+                product.category == *category && product.class.as_ref().is_none_or(|c| c == class)
+            })
+            .map(ProductId)
+    }
+
+    ///Products a client would accept for `category`: the one made for
+    /// their class first, then the rest of the category as substitutes.
+    pub fn candidates(&self, category: &Prioritization, class: &ClassType) -> Vec<ProductId> {
+        let preferred = self.find(category, class);
+
+        preferred
+            .into_iter()
+            .chain(
+                self.ids()
+                    .filter(|&id| self.get(id).category == *category && Some(id) != preferred),
+            )
+            .collect()
+    }
+
+    //True when `id` starts a new group (e.g. a new class) in UI listings
+    pub fn starts_group(&self, id: ProductId) -> bool {
+        id.0 > 0 && self.products[id.0 - 1].class != self.products[id.0].class
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //A one-product catalog with `field` replaced
+    fn catalog_with(field: &str, value: serde_json::Value) -> Result<Catalog, String> {
+        let mut product = serde_json::json!({
+            "name": "Bread",
+            "category": "Food",
+            "class": null,
+            "base_cost": 5,
+            "recipe": { "Grain": 2 },
+            "baseline_demand": 5,
+            "upgrade_costs": { "good": 1, "exceptional": 2 },
+            "icon": "meal.png"
+        });
+        product[field] = value;
+
+        Catalog::from_json(serde_json::json!([product]).to_string().as_bytes())
+    }
+
+    #[test]
+    fn bundled_catalog_is_valid() {
+        assert!(!Catalog::default().is_empty());
+    }
+
+    #[test]
+    fn missing_fields_use_defaults() {
+        let catalog = catalog_with("name", "Bread".into()).unwrap();
+        let bread = catalog.get(catalog.find_by_name("Bread").unwrap());
+
+        assert_eq!(bread.space, 1);
+        assert_eq!(bread.lead_time, 0.0);
+        assert!(bread.shelf_life.is_none());
+        assert_eq!(bread.standard_cost(), 7);
+    }
+
+    #[test]
+    fn rejects_invalid_products() {
+        assert!(Catalog::from_json(b"[]").is_err());
+        assert!(Catalog::from_json(b"not json").is_err());
+
+        assert!(catalog_with("base_cost", 0.into()).is_err());
+        assert!(catalog_with("recipe", serde_json::json!({ "Iron": 0 })).is_err());
+        assert!(catalog_with("space", 0.into()).is_err());
+        assert!(catalog_with("lead_time", (-1.0).into()).is_err());
+        assert!(catalog_with("shelf_life", 0.0.into()).is_err());
+        assert!(catalog_with("depreciation", 1.0.into()).is_err());
+    }
+}
@@ -1,32 +1,74 @@
 use notan::draw::{Draw, DrawImages};
 use notan::graphics::Texture;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone)]
+use crate::catalog::{Catalog, ProductId};
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ClassType {
-    FIGHTER,
-    CLERIC,
-    MAGE,
+    Fighter,
+    Cleric,
+    Mage,
+}
+
+impl ClassType {
+    pub fn name(&self) -> &str {
+        match self {
+            ClassType::Fighter => "Fighter",
+            ClassType::Mage => "Mage",
+            ClassType::Cleric => "Cleric",
+        }
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Prioritization {
     Food,
     Armor,
     Weapon,
 }
 
-#[derive(Clone)]
+impl Prioritization {
+    pub const ALL: [Prioritization; 3] = [
+        Prioritization::Food,
+        Prioritization::Armor,
+        Prioritization::Weapon,
+    ];
+
+    pub fn name(&self) -> &str {
+        match self {
+            Prioritization::Food => "Food",
+            Prioritization::Armor => "Armor",
+            Prioritization::Weapon => "Weapons",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct BasketItem {
+    pub category: Prioritization,
+    //Share of the client's income set aside for this item
+    pub budget: i32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Client {
     class_type: ClassType,
     price_factor: f32,   //demand goes down the higher this is
     quality_factor: f32, //demand goes up the higher this is
-    prioritization: Prioritization,
+    //Most wanted first
+    basket: Vec<BasketItem>,
     income: i32,
     exit: bool,
     complete: bool,
     position: (f32, f32),
+    previous_position: (f32, f32),
     purchase_complete: bool,
     reached_center: bool,
+    //Left the line unserved, so walks back out through the entrance
+    gave_up: bool,
+    //Seconds the client will wait in line before leaving unserved
+    patience: f32,
 }
 
 impl Client {
@@ -34,8 +76,9 @@ impl Client {
         class_type: ClassType,
         price_factor: f32,
         quality_factor: f32,
-        prioritization: Prioritization,
+        basket: Vec<BasketItem>,
         income: i32,
+        patience: f32,
     ) -> Self {
         Self {
             reached_center: false,
@@ -44,10 +87,13 @@ impl Client {
             price_factor,
             complete: false,
             exit: false,
-            position: (-200.0, -40.0),
+            position: (Self::ENTRANCE_X, -40.0),
+            previous_position: (Self::ENTRANCE_X, -40.0),
             income,
-            prioritization,
+            basket,
             purchase_complete: false,
+            gave_up: false,
+            patience,
         }
     }
 
@@ -67,50 +113,70 @@ impl Client {
         self.position.1 = val;
     }
 
-    pub fn want(&self) -> String {
-        let mage = ["Mage Armor", "Mage Weapons"];
-        let fighter = ["Fighter Armor", "Fighter Weapons"];
-        let cleric = ["Cleric Armor", "Cleric Weapons"];
-
-        let index: Option<usize> = match self.prioritization {
-            Prioritization::Food => None,
-            Prioritization::Armor => Some(0),
-            Prioritization::Weapon => Some(1),
-        };
+    //The product the client wants most
+    pub fn want(&self, catalog: &Catalog) -> Option<ProductId> {
+        let item = self.basket.first()?;
+        catalog.find(&item.category, &self.class_type)
+    }
 
-        if let Some(index) = index {
-            match self.class_type {
-                ClassType::FIGHTER => fighter[index].to_string(),
-                ClassType::CLERIC => cleric[index].to_string(),
-                ClassType::MAGE => mage[index].to_string(),
-            }
-        } else {
-            String::from("Food")
-        }
+    pub fn basket(&self) -> &[BasketItem] {
+        &self.basket
     }
 
-    pub fn draw(&mut self, texture: &Texture, gfx: &mut Draw) {
-        gfx.image(texture)
-            .size(315.0, 315.0)
-            .position(self.position.0, self.position.1);
+    //Pixels per second the client walks across the shop
+    const WALK_SPEED: f32 = 300.0;
+
+    //Width and height the client is drawn at
+    const SIZE: f32 = 315.0;
+
+    //Where clients walk in from the left of the screen
+    const ENTRANCE_X: f32 = -200.0;
+
+    //Where the client stands when served
+    pub const COUNTER_X: f32 = 200.0;
+
+    //Horizontal gap between clients waiting in line. With
+    // Simulation::MAX_QUEUE the back of the line stays on screen.
+    pub const QUEUE_SPACING: f32 = 60.0;
+
+    //place: 0 for the counter, 1 for the first spot behind it and so on
+    pub fn walk(&mut self, dt: f32, place: usize) {
+        self.previous_position = self.position;
+        let step = Self::WALK_SPEED * dt;
 
         if !self.exit {
-            if self.position.0 >= 200.0 {
-                self.position.0 = 200.0;
-                self.reached_center = true;
+            let target = Self::COUNTER_X - place as f32 * Self::QUEUE_SPACING;
+
+            if self.position.0 >= target {
+                self.position.0 = self.position.0.max(target);
+                self.reached_center = place == 0;
             } else {
-                self.position.0 += 5.0;
+                self.position.0 = (self.position.0 + step).min(target);
             }
-        } else {
-            if self.position.0 >= 800.0 {
-                self.position.0 = 800.0;
+        } else if self.gave_up {
+            if self.position.0 <= -Self::SIZE {
                 self.complete = true;
             } else {
-                self.position.0 += 5.0;
+                self.position.0 -= step;
             }
+        } else if self.position.0 >= 800.0 {
+            self.position.0 = 800.0;
+            self.complete = true;
+        } else {
+            self.position.0 += step;
         }
     }
 
+    //alpha: how far between the last two simulation ticks we are rendering
+    pub fn draw(&self, texture: &Texture, gfx: &mut Draw, alpha: f32) {
+        let (prev_x, prev_y) = self.previous_position;
+        let (x, y) = self.position;
+
+        gfx.image(texture)
+            .size(Self::SIZE, Self::SIZE)
+            .position(prev_x + (x - prev_x) * alpha, prev_y + (y - prev_y) * alpha);
+    }
+
     pub fn center(&self) -> bool {
         self.reached_center
     }
@@ -120,11 +186,7 @@ impl Client {
     }
 
     pub fn get_class_type_name(&self) -> &str {
-        match self.class_type {
-            ClassType::FIGHTER => "Fighter",
-            ClassType::MAGE => "Mage",
-            ClassType::CLERIC => "Cleric",
-        }
+        self.class_type.name()
     }
 
     pub fn price_factor(&self) -> f32 {
@@ -150,6 +212,12 @@ impl Client {
         self.exit = true;
     }
 
+    //Leaves the line without being served
+    pub fn give_up(&mut self) {
+        self.exit = true;
+        self.gave_up = true;
+    }
+
     pub fn exit(&self) -> bool {
         self.exit
     }
@@ -165,4 +233,14 @@ impl Client {
     pub fn is_purchase_complete(&self) -> bool {
         self.purchase_complete
     }
+
+    ///Counts down the client's patience, returning true once it runs out.
+    pub fn wait(&mut self, dt: f32) -> bool {
+        self.patience -= dt;
+        self.patience <= 0.0
+    }
+
+    pub fn patience(&self) -> f32 {
+        self.patience
+    }
 }
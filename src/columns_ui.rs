@@ -1,7 +1,6 @@
 ///Column UI refers to two columns where the user
 /// can drag and drop labels between each column.
 /// This is used for the items inventory, and for assigning activities.
-
 pub struct ColumnUI<'a, T> {
     pub first: &'a mut Vec<T>,
     pub second: &'a mut Vec<T>,
@@ -44,11 +43,10 @@ impl<'a, T: Clone + ColumnItem> ColumnUI<'a, T> {
     //Category is used to make unique ids.
     pub fn show(&mut self, uis: &mut [notan_egui::Ui]) {
         let frame = notan_egui::Frame::default().inner_margin(1.0);
-        for i in 0..2 {
+        for (i, ui) in uis.iter_mut().enumerate().take(2) {
             let mut from_location: Option<ColumnLocation> = None;
             let mut to_location: Option<ColumnLocation> = None;
 
-            let ui = &mut uis[i];
             let (is_first_column, current_list, heading) = if i == 0 {
                 (true, self.first.clone(), self.first_name.as_str())
             } else {
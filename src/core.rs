@@ -1,12 +1,20 @@
-use core::f32;
+use std::collections::HashMap;
 
 use notan::draw::DrawImages;
 use notan::draw::*;
 use notan::prelude::*;
 use notan_egui::*;
 
-use crate::business::Business;
-use crate::client::{ClassType, Client, Prioritization};
+use crate::bank::LoanDraft;
+use crate::catalog::Catalog;
+use crate::client::{ClassType, Prioritization};
+use crate::events::EventKind;
+use crate::history::ChartMetric;
+use crate::marketing::CampaignDraft;
+use crate::materials::Material;
+use crate::reputation::Reputation;
+use crate::save;
+use crate::simulation::{Difficulty, Simulation};
 
 #[derive(AppState)]
 pub struct Core {
@@ -18,27 +26,44 @@ pub struct Core {
     credit_icons: [SizedTexture; 3],
     class_characters: [Option<Texture>; 3],
 
-    window_states: [bool; 5],
+    catalog_loaded: bool,
+    icon_assets: Option<AssetList>,
+    //Keyed by the icon path in the product catalog
+    product_icons: HashMap<String, SizedTexture>,
+
+    //Indexed by the Core::*_WINDOW constants
+    window_states: [bool; Core::WINDOW_COUNT],
+    save_status: String,
+
+    //Indexed by EventKind
+    log_filter: [bool; EventKind::ALL.len()],
+    show_full_history: bool,
+    //Price the player counters a haggling client with
+    counter_price: i32,
+    finances_day: u32,
+    campaign_draft: CampaignDraft,
+    campaign_status: String,
+    loan_draft: LoanDraft,
+    loan_status: String,
+    //Units to buy, indexed by Material
+    material_amounts: [i32; Material::ALL.len()],
+    material_status: String,
+    chart_metric: ChartMetric,
+    //Indexed by ProductId
+    chart_products: Vec<bool>,
 
     bg_music: AudioSource,
     bg_sound: Option<Sound>,
     volume: f32,
 
     start_game: bool,
+    seed: u64,
 
     time: f32,
 
-    production_time: f32,
-
-    business: Business,
-    client: Option<Client>,
-    log: Vec<String>,
-
-    mage_probability: f32,
-    fighter_probability: f32,
-
-    food_probability: f32,
-    weapon_probability: f32,
+    //Unsimulated time carried over between frames
+    tick_accumulator: f32,
+    simulation: Simulation,
 
     font: Font
 }
@@ -48,12 +73,34 @@ impl Core {
     const MAGE_TEXTURE: usize = 1;
     const CLERIC_TEXTURE: usize = 2;
 
+    //Which windows are open, toggled from the bottom panel
+    const PRICES_WINDOW: usize = 0;
+    const ALLOCATION_WINDOW: usize = 1;
+    const SUPPLY_WINDOW: usize = 2;
+    const SETTINGS_WINDOW: usize = 3;
+    const CLIENT_WINDOW: usize = 4;
+    const SAVE_WINDOW: usize = 5;
+    const FINANCES_WINDOW: usize = 6;
+    const CHARTS_WINDOW: usize = 7;
+    const PRODUCTION_WINDOW: usize = 8;
+    const RESEARCH_WINDOW: usize = 9;
+    const MARKETING_WINDOW: usize = 10;
+    const MARKET_WINDOW: usize = 11;
+    const BANK_WINDOW: usize = 12;
+    const MATERIALS_WINDOW: usize = 13;
+    const WINDOW_COUNT: usize = 14;
+
+    //Caps catch-up work after a long frame (e.g. the browser tab was hidden)
+    const MAX_TICKS_PER_FRAME: u32 = 10;
+
     pub fn new(app: &mut App, assets: &mut Assets, graphics: &mut Graphics) -> Self {
         
         let programmer = graphics.create_texture().from_image(include_bytes!("../assets/lead_programmer.png")).with_premultiplied_alpha().build().unwrap();
         let artist = graphics.create_texture().from_image(include_bytes!("../assets/artist.png")).with_premultiplied_alpha().build().unwrap();
         let musician = graphics.create_texture().from_image(include_bytes!("../assets/musician.png")).with_premultiplied_alpha().build().unwrap();
         
+        let seed = rand::thread_rng().gen();
+
         Self {
             font: graphics.create_font(include_bytes!("../assets/font/MajorMonoDisplay-Regular.ttf")).unwrap(),
             loaded_assets: assets
@@ -66,15 +113,20 @@ impl Core {
                     "../assets/magegirl.png",
                     "../assets/lead_programmer.png",
                     "../assets/artist.png",
-                    "../assets/musician.png"
+                    "../assets/musician.png",
+                    Catalog::PATH,
                 ])
                 .unwrap(),
+            catalog_loaded: false,
+            icon_assets: None,
+            product_icons: HashMap::new(),
             background_texture: None,
             foreground_texture: None,
             background_characters: None,
             credit_icons: [graphics.egui_register_texture(&programmer), graphics.egui_register_texture(&artist), graphics.egui_register_texture(&musician)],
             
             time: 0.0,
+            tick_accumulator: 0.0,
 
             volume: 1.0,
 
@@ -85,58 +137,102 @@ impl Core {
 
             bg_sound: None,
 
-            window_states: [false, false, false, false, true],
+            window_states: std::array::from_fn(|window| window == Self::CLIENT_WINDOW),
+            save_status: String::new(),
+            log_filter: [true; EventKind::ALL.len()],
+            show_full_history: false,
+            counter_price: 0,
+            finances_day: 0,
+            campaign_draft: CampaignDraft::default(),
+            campaign_status: String::new(),
+            loan_draft: LoanDraft::default(),
+            loan_status: String::new(),
+            material_amounts: [10; Material::ALL.len()],
+            material_status: String::new(),
+            chart_metric: ChartMetric::Funds,
+            chart_products: Vec::new(),
 
             start_game: false,
+            seed,
 
-            business: Business::new(5_000),
-            client: None,
+            simulation: Simulation::new(Difficulty::Hard.starting_funds(), seed, Catalog::default()),
             class_characters: [None, None, None],
-
-            production_time: 0.0,
-
-            mage_probability: 0.3,
-            fighter_probability: 0.3,
-            food_probability: 0.3,
-            weapon_probability: 0.3,
-            log: Vec::new(),
         }
     }
 
-    fn create_character(&mut self) {
-        self.log.clear();
-        let rng = rand::thread_rng().gen_range(0.0..1.0);
+    //Reads .json assets as text so data files can be parsed once loaded
+    pub fn create_text_loader() -> AssetLoader {
+        AssetLoader::new()
+            .use_parser(|_id: &str, data: Vec<u8>| {
+                String::from_utf8(data).map_err(|e| e.to_string())
+            })
+            .extension("json")
+    }
 
-        let class = if rng < self.mage_probability {
-            ClassType::MAGE
-        } else if rng < self.mage_probability + self.fighter_probability {
-            ClassType::FIGHTER
-        } else {
-            ClassType::CLERIC
+    fn load_catalog(&mut self, assets: &mut Assets) {
+        let catalog = match self.loaded_assets.take::<String>(Catalog::PATH) {
+            Ok(asset) => Catalog::from_json(asset.try_unwrap().unwrap().as_bytes()),
+            Err(err) => Err(err),
         };
 
-        let rng = rand::thread_rng().gen_range(0.0..1.0);
+        let catalog = catalog.unwrap_or_else(|err| {
+            notan::log::error!("Failed to load products! {}", err);
+            Catalog::default()
+        });
 
-        let priority = if rng < self.food_probability {
-            Prioritization::Food
-        } else if rng < self.food_probability + self.weapon_probability {
-            Prioritization::Weapon
-        } else {
-            Prioritization::Armor
+        let icon_paths: Vec<String> = catalog
+            .ids()
+            .map(|id| format!("../assets/{}", catalog.get(id).icon))
+            .collect();
+        let icon_paths: Vec<&str> = icon_paths.iter().map(String::as_str).collect();
+
+        self.icon_assets = match assets.load_list(&icon_paths) {
+            Ok(list) => Some(list),
+            Err(err) => {
+                notan::log::error!("Failed to load product icons! {}", err);
+                None
+            }
         };
 
-        let income = rand::thread_rng().gen_range(50..=100);
+        self.simulation =
+            Simulation::new(Difficulty::Hard.starting_funds(), self.seed, catalog);
+    }
 
-        self.client = Some(Client::new(class, 0.5, 0.4, priority, income));
+    fn register_product_icons(&mut self, graphics: &mut Graphics) {
+        let Some(icon_assets) = &mut self.icon_assets else {
+            return;
+        };
+
+        if !icon_assets.is_loaded() {
+            return;
+        }
+
+        let catalog = self.simulation.business().catalog();
+        for id in catalog.ids() {
+            let icon = &catalog.get(id).icon;
+            if let Ok(asset) = icon_assets.take::<Texture>(&format!("../assets/{}", icon)) {
+                let texture = asset.try_unwrap().unwrap();
+                self.product_icons
+                    .insert(icon.clone(), graphics.egui_register_texture(&texture));
+            }
+        }
+
+        self.icon_assets = None;
     }
 
-    pub fn update(app: &mut App, state: &mut Core) {
+    pub fn update(app: &mut App, assets: &mut Assets, state: &mut Core) {
         if !state.loaded_assets.is_loaded() {
             return;
         }
 
+        if !state.catalog_loaded {
+            state.catalog_loaded = true;
+            state.load_catalog(assets);
+        }
+
         if app.keyboard.was_pressed(KeyCode::Space) && !state.start_game {
             state.start_game = true;
+            state.simulation.set_seed(state.seed);
         }
 
         if !state.start_game {
@@ -148,14 +244,12 @@ impl Core {
             state.bg_sound = Some(app.audio.play_sound(&state.bg_music, 1.0, true));
         }
 
-        let mut load_texture = |path| {
-            return match state.loaded_assets.take::<Texture>(path) {
-                Ok(asset) => Some(asset.try_unwrap().unwrap()),
-                Err(err) => {
-                    notan::log::error!("Failed to load image! {}", err);
-                    None
-                }
-            };
+        let mut load_texture = |path| match state.loaded_assets.take::<Texture>(path) {
+            Ok(asset) => Some(asset.try_unwrap().unwrap()),
+            Err(err) => {
+                notan::log::error!("Failed to load image! {}", err);
+                None
+            }
         };
 
         let bg_empty = state.background_texture.is_none();
@@ -209,120 +303,28 @@ impl Core {
             state.time = 0.0;
         }
 
-        state.production_time += app.timer.delta_f32();
-
-        if state.production_time >= 5.0 {
-            state.production_time = 0.0;
-            state.business.update_quantities();
-        }
-
         if let Some(sound) = &state.bg_sound {
             app.audio.set_volume(sound, state.volume);
         }
 
-        'client_buy: {
-            if let Some(client) = &mut state.client {
-                if client.is_complete() {
-                    state.create_character();
-                } else if !client.is_purchase_complete() && client.center() {
-                    let income = client.income();
-                    let want = client.want();
-
-                    state.business.update_demand(
-                        client.price_factor(),
-                        client.quality_factor(),
-                        &mut state.log,
-                    );
-
-                    let price = state.business.get_price(&want);
-                    let demand = state.business.get_demand(&want);
-
-                    let stock = state.business.get_quantity(&want);
-
-                    state.log.push(format!(
-                        "Client [{}] appears.",
-                        client.get_class_type_name()
-                    ));
-
-                    state.log.push(format!("Client desires [{}].", want));
-
-                    if price == 0 {
-                        state
-                            .log
-                            .push(format!("[{}] is not for sale. (Price is set to 0)", want));
-                        client.complete_purchase();
-                        break 'client_buy;
-                    }
-
-                    //If stock is negative, there's a problem.
-                    if stock <= 0 {
-                        state
-                            .log
-                            .push(format!("You do not have any [{}] available.", want));
-                        client.complete_purchase();
-                        break 'client_buy;
-                    }
-
-                    let quantity = (demand as f32 / 2.0) * (income as f32 / price as f32);
-                    let quantity_multiplier = 1.0 + rand::thread_rng().gen_range(-0.5..=0.75);
-                    let final_quantity = quantity * quantity_multiplier;
-                    let final_quantity = final_quantity as i32;
-                    notan::log::debug!("{demand}");
-                    let final_quantity = final_quantity.min(demand);
-
-                    if final_quantity == 0 {
-                        state.log.push(
-                            "Client does not desire anything from your business.".to_string(),
-                        );
-                        state.log.push(format!(
-                            "Either there is not enough demand for [{}], or the price is too high.",
-                            want
-                        ));
-                        client.complete_purchase();
-                        break 'client_buy;
-                    }
-
-                    if price > income {
-                        state.log.push(format!(
-                            "Price of [{}] is more than client is willing to spend.",
-                            want
-                        ));
-                    }
-
-                    state.log.push(format!(
-                        "Client seeks to buy {} unit(s) of [{}].",
-                        final_quantity, want
-                    ));
-
-                    if final_quantity > stock {
-                        state.log.push("Client desires more than is currently available. Client will buy what is remaining.".to_string());
-                    }
-
-                    let e = f32::consts::E;
-                    let purchase_probability =
-                        demand as f32 / (demand as f32 + e.powi((price - income).max(-5)));
-
-                    let rng = rand::thread_rng().gen_range(0.0..=1.0);
-                    if rng < purchase_probability {
-                        state.log.push(format!("Client has made purchase!"));
-                        state
-                            .business
-                            .purchase(&want, final_quantity, &mut state.log);
-                    } else {
-                        state.log.push(format!("Client did not make purchase."));
-                    }
+        state.tick_accumulator += app.timer.delta_f32();
 
-                    client.complete_purchase();
-                }
+        let mut ticks = 0;
+        while state.tick_accumulator >= Simulation::TICK {
+            if ticks == Self::MAX_TICKS_PER_FRAME {
+                state.tick_accumulator = 0.0;
+                break;
             }
-        }
 
-        if state.client.is_none() {
-            state.create_character();
+            state.simulation.step();
+            state.tick_accumulator -= Simulation::TICK;
+            ticks += 1;
         }
     }
 
     pub fn draw(graphics: &mut Graphics, plugins: &mut Plugins, state: &mut Core) {
+        state.register_product_icons(graphics);
+
         let mut bg = graphics.create_draw();
         bg.clear(Color::from_rgb(0.1, 0.1, 0.1));
 
@@ -351,29 +353,31 @@ impl Core {
         graphics.render(&bg);
 
         let mut character = graphics.create_draw();
+        let alpha = state.tick_accumulator / Simulation::TICK;
 
-        if let Some(client) = &mut state.client {
+        //Back of the line first so the client at the counter is drawn on top
+        for client in state.simulation.clients().rev() {
             match client.get_class_type() {
-                ClassType::FIGHTER => {
+                ClassType::Fighter => {
                     if let Some(fighter) = &state.class_characters[Self::FIGHTER_TEXTURE] {
-                        client.draw(fighter, &mut character);
+                        client.draw(fighter, &mut character, alpha);
                     }
                 }
-                ClassType::MAGE => {
+                ClassType::Mage => {
                     if let Some(mage) = &state.class_characters[Self::MAGE_TEXTURE] {
-                        client.draw(mage, &mut character);
+                        client.draw(mage, &mut character, alpha);
                     }
                 }
-                ClassType::CLERIC => {
+                ClassType::Cleric => {
                     if let Some(cleric) = &state.class_characters[Self::CLERIC_TEXTURE] {
-                        client.draw(cleric, &mut character);
+                        client.draw(cleric, &mut character, alpha);
                     }
                 }
             };
-
-            graphics.render(&character);
         }
 
+        graphics.render(&character);
+
         let mut fg = graphics.create_draw();
         if let Some(fg_image) = &state.foreground_texture {
             fg.image(fg_image).size(800.0, 480.0);
@@ -394,13 +398,23 @@ impl Core {
                         ui.separator();
                         ui.columns(3, |uis| {
                             if uis[0].button("Easy Mode").on_hover_ui(|ui| { ui.label("Start off with $200,000. Recommended to learn since I had no time to balance anything."); }).clicked() {
-                                state.business.set_funds(200_000);    
+                                state.simulation.business_mut().set_funds(Difficulty::Easy.starting_funds());    
                             }
                             if uis[1].button("Medium Mode").on_hover_ui(|ui| { ui.label("Start off with $10,000. You should be better now."); }).clicked() {
-                                state.business.set_funds(10_000);    
+                                state.simulation.business_mut().set_funds(Difficulty::Medium.starting_funds());    
                             }
                             if uis[2].button("Hard Mode").on_hover_ui(|ui| { ui.label("Start off with $5,000. Is this even feasible?"); }).clicked() {
-                                state.business.set_funds(5_000);    
+                                state.simulation.business_mut().set_funds(Difficulty::Hard.starting_funds());    
+                            }
+                        });
+
+                        ui.separator();
+
+                        ui.horizontal(|ui| {
+                            ui.label("Seed:");
+                            ui.add(DragValue::new(&mut state.seed)).on_hover_ui(|ui| { ui.label("The same seed and the same decisions always lead to the same run."); });
+                            if ui.button("Randomize").clicked() {
+                                state.seed = rand::thread_rng().gen();
                             }
                         });
 
@@ -436,64 +450,319 @@ impl Core {
                 return;
             }
 
+            if state.simulation.is_bankrupt() {
+                Window::new("Bankrupt")
+                    .resizable(false)
+                    .movable(false)
+                    .collapsible(false)
+                    .pivot(Align2::CENTER_CENTER)
+                    .fixed_pos(pos2(400.0, 240.0))
+                    .show(ctx, |ui| {
+                        ui.vertical_centered(|ui| {
+                            ui.heading("Game Over");
+                            ui.label(format!("The shop could not pay its debts and closed on day {}.", state.simulation.day() + 1));
+                            ui.label(format!("Funds: ${}", state.simulation.business().funds()));
+                            ui.label(format!("Debt: ${}", state.simulation.business().bank().debt()));
+
+                            if ui.button("Back to Title").clicked() {
+                                let catalog = state.simulation.business().catalog().clone();
+                                state.simulation = Simulation::new(Difficulty::Hard.starting_funds(), state.seed, catalog);
+                                state.start_game = false;
+                            }
+                        });
+                    });
+                return;
+            }
+
+            TopBottomPanel::top("top")
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        let date = state.simulation.date();
+                        ui.label(date.name()).on_hover_ui(|ui| {
+                            ui.label("Demand this season:");
+                            for category in Prioritization::ALL {
+                                ui.label(format!("{}: x{:.2}", category.name(), date.season().demand_modifier(category)));
+                            }
+                        });
+                        ui.separator();
+                        ui.label(format!("Funds: ${}", state.simulation.business().funds()));
+                        ui.separator();
+
+                        let reputation = state.simulation.reputation();
+                        ui.horizontal(|ui| {
+                            ui.spacing_mut().item_spacing.x = 0.0;
+                            for star in 1..=Reputation::MAX_STARS as u32 {
+                                let color = if reputation.stars() + 0.5 >= star as f32 { Color32::GOLD } else { Color32::DARK_GRAY };
+                                ui.label(RichText::new("⭐").color(color));
+                            }
+                        })
+                        .response
+                        .on_hover_ui(|ui| {
+                            ui.label(format!("Reputation: {:.1}/{}", reputation.stars(), Reputation::MAX_STARS));
+                            for class in Reputation::CLASSES {
+                                ui.label(format!("{}s: {:.1}", class.name(), reputation.class_stars(class)));
+                            }
+                        });
+                    });
+                });
+
             TopBottomPanel::bottom("bottom")
                 .resizable(false)
                 .show(ctx, |ui| {
-                    ui.columns(5, |uis| {
-                        uis[0].toggle_value(&mut state.window_states[0], "Price Levels");
-                        uis[1].toggle_value(&mut state.window_states[1], "Fund Allocation");
-                        uis[2].toggle_value(&mut state.window_states[2], "Supply");
-                        uis[3].toggle_value(&mut state.window_states[3], "Settings");
-                        uis[4].toggle_value(&mut state.window_states[4], "Client");
+                    ui.horizontal_wrapped(|ui| {
+                        ui.toggle_value(&mut state.window_states[Self::PRICES_WINDOW], "Price Levels");
+                        ui.toggle_value(&mut state.window_states[Self::ALLOCATION_WINDOW], "Fund Allocation");
+                        ui.toggle_value(&mut state.window_states[Self::SUPPLY_WINDOW], "Supply");
+                        ui.toggle_value(&mut state.window_states[Self::PRODUCTION_WINDOW], "Production");
+                        ui.toggle_value(&mut state.window_states[Self::MATERIALS_WINDOW], "Materials");
+                        ui.toggle_value(&mut state.window_states[Self::RESEARCH_WINDOW], "Research");
+                        ui.toggle_value(&mut state.window_states[Self::MARKETING_WINDOW], "Marketing");
+                        ui.toggle_value(&mut state.window_states[Self::MARKET_WINDOW], "Market");
+                        ui.toggle_value(&mut state.window_states[Self::FINANCES_WINDOW], "Finances");
+                        ui.toggle_value(&mut state.window_states[Self::BANK_WINDOW], "Bank");
+                        ui.toggle_value(&mut state.window_states[Self::CHARTS_WINDOW], "Charts");
+                        ui.toggle_value(&mut state.window_states[Self::SETTINGS_WINDOW], "Settings");
+                        ui.toggle_value(&mut state.window_states[Self::SAVE_WINDOW], "Save/Load");
+                        ui.toggle_value(&mut state.window_states[Self::CLIENT_WINDOW], "Client");
                     });
                 });
 
-            if state.window_states[4] {
-                if let Some(client) = &mut state.client {
-                    Window::new("Client")
-                        .resizable(false)
-                        .collapsible(false)
-                        .show(ctx, |ui| {
-                            ScrollArea::vertical().show(ui, |ui| {
-                                for entry in state.log.iter() {
-                                    ui.label(entry);
+            if state.window_states[Self::CLIENT_WINDOW] && state.simulation.client().is_some() {
+                Window::new("Client")
+                    .resizable(false)
+                    .collapsible(false)
+                    .show(ctx, |ui| {
+                        ui.horizontal(|ui| {
+                            for kind in EventKind::ALL {
+                                ui.toggle_value(&mut state.log_filter[kind as usize], kind.name());
+                            }
+                            ui.checkbox(&mut state.show_full_history, "History")
+                                .on_hover_text("Show events from previous clients too.");
+                        });
+
+                        ui.label(format!("Waiting in line: {}", state.simulation.queue_length()));
+
+                        ui.separator();
+
+                        let since = if state.show_full_history {
+                            0.0
+                        } else {
+                            state.simulation.visit_start()
+                        };
+                        let catalog = state.simulation.business().catalog();
+
+                        ScrollArea::vertical().stick_to_bottom(true).show(ui, |ui| {
+                            for logged in state.simulation.log().since(since) {
+                                if !state.log_filter[logged.event.kind() as usize] {
+                                    continue;
                                 }
-                            });
-                            if ui.button("Complete Order").clicked() {
-                                client.complete();
+
+                                let seconds = logged.time as u32;
+                                ui.label(format!(
+                                    "[{:02}:{:02}] {}",
+                                    seconds / 60,
+                                    seconds % 60,
+                                    logged.event.describe(catalog)
+                                ));
                             }
                         });
-                }
+
+                        if let Some(haggle) = state.simulation.haggle() {
+                            let product = haggle.product();
+                            let offer = haggle.offer();
+                            let asking_price = haggle.asking_price();
+                            let counters_left = haggle.counters_left();
+                            state.counter_price = state.counter_price.clamp(offer, asking_price);
+
+                            ui.separator();
+                            ui.label(format!(
+                                "Client offers ${} for one [{}] (asking ${}).",
+                                offer,
+                                state.simulation.business().catalog().name(product),
+                                asking_price
+                            ));
+
+                            ui.horizontal(|ui| {
+                                if ui.button("Accept").clicked() {
+                                    state.simulation.accept_offer();
+                                }
+                                if ui.button("Reject").clicked() {
+                                    state.simulation.reject_offer();
+                                }
+
+                                ui.add(DragValue::new(&mut state.counter_price).prefix("$").clamp_range(offer..=asking_price));
+                                if ui.button("Counter").on_hover_text(format!("{} counter-offer(s) left before the client walks away.", counters_left)).clicked() {
+                                    state.simulation.counter_offer(state.counter_price);
+                                }
+                            });
+                        }
+
+                        if ui.button("Complete Order").clicked() {
+                            state.simulation.complete_order();
+                        }
+                    });
             }
 
-            if state.window_states[0] {
+            if state.window_states[Self::PRICES_WINDOW] {
                 Window::new("Price Levels")
                     .resizable(false)
                     .collapsible(false)
                     .show(ctx, |ui| {
-                        state.business.show_prices(ui);
+                        state.simulation.business_mut().show_prices(ui);
                     });
             }
 
-            if state.window_states[1] {
+            if state.window_states[Self::ALLOCATION_WINDOW] {
                 Window::new("Fund Allocation")
                     .resizable(false)
                     .collapsible(false)
                     .show(ctx, |ui| {
-                        state.business.show_allocation(ui);
+                        state.simulation.business_mut().show_allocation(ui);
                     });
             }
 
-            if state.window_states[2] {
+            if state.window_states[Self::SUPPLY_WINDOW] {
                 Window::new("Supply")
                     .resizable(false)
                     .collapsible(false)
                     .show(ctx, |ui| {
-                        state.business.show_supply(ui);
+                        state.simulation.business_mut().show_supply(ui, &state.product_icons);
+                    });
+            }
+
+            if state.window_states[Self::PRODUCTION_WINDOW] {
+                Window::new("Production")
+                    .resizable(false)
+                    .collapsible(false)
+                    .show(ctx, |ui| {
+                        state.simulation.business().show_production(ui);
+                    });
+            }
+
+            if state.window_states[Self::MATERIALS_WINDOW] {
+                Window::new("Materials")
+                    .resizable(false)
+                    .collapsible(false)
+                    .show(ctx, |ui| {
+                        let bought = state.simulation.business().materials().show(ui, &mut state.material_amounts);
+
+                        if let Some((material, units)) = bought {
+                            state.material_status = match state.simulation.buy_material(material, units) {
+                                Ok(()) => String::new(),
+                                Err(err) => err,
+                            };
+                        }
+
+                        if !state.material_status.is_empty() {
+                            ui.label(&state.material_status);
+                        }
+                    });
+            }
+
+            if state.window_states[Self::RESEARCH_WINDOW] {
+                Window::new("Research")
+                    .resizable(false)
+                    .collapsible(false)
+                    .show(ctx, |ui| {
+                        if let Some(node) = state.simulation.business().research().show(ui) {
+                            let _ = state.simulation.unlock_research(node);
+                        }
                     });
             }
 
-            if state.window_states[3] {
+            if state.window_states[Self::MARKETING_WINDOW] {
+                Window::new("Marketing")
+                    .resizable(false)
+                    .collapsible(false)
+                    .show(ctx, |ui| {
+                        let launch = state.simulation.business().marketing().show(
+                            ui,
+                            &mut state.campaign_draft,
+                            state.simulation.business().catalog(),
+                            state.simulation.elapsed(),
+                        );
+
+                        if launch {
+                            state.campaign_status = match state.simulation.launch_campaign(&state.campaign_draft) {
+                                Ok(()) => String::new(),
+                                Err(err) => err,
+                            };
+                        }
+
+                        if !state.campaign_status.is_empty() {
+                            ui.label(&state.campaign_status);
+                        }
+                    });
+            }
+
+            if state.window_states[Self::MARKET_WINDOW] {
+                Window::new("Market")
+                    .resizable(false)
+                    .collapsible(false)
+                    .show(ctx, |ui| {
+                        state.simulation.market().show(ui, state.simulation.business());
+                    });
+            }
+
+            if state.window_states[Self::BANK_WINDOW] {
+                Window::new("Bank")
+                    .resizable(false)
+                    .collapsible(false)
+                    .show(ctx, |ui| {
+                        let borrow = state.simulation.business().bank().show(
+                            ui,
+                            &mut state.loan_draft,
+                            state.simulation.business().funds(),
+                        );
+
+                        if borrow {
+                            state.loan_status = match state.simulation.take_loan(&state.loan_draft) {
+                                Ok(()) => String::new(),
+                                Err(err) => err,
+                            };
+                        }
+
+                        if !state.loan_status.is_empty() {
+                            ui.label(&state.loan_status);
+                        }
+                    });
+            }
+
+            if state.window_states[Self::FINANCES_WINDOW] {
+                Window::new("Finances")
+                    .resizable(false)
+                    .collapsible(false)
+                    .show(ctx, |ui| {
+                        state.simulation.business().ledger().show(
+                            ui,
+                            &mut state.finances_day,
+                            state.simulation.day(),
+                            Simulation::DAY_LENGTH,
+                        );
+
+                        ui.separator();
+                        ui.strong("Upcoming Bills");
+                        state.simulation.business().show_bills(ui, state.simulation.day());
+                    });
+            }
+
+            if state.window_states[Self::CHARTS_WINDOW] {
+                Window::new("Charts")
+                    .resizable(false)
+                    .collapsible(false)
+                    .show(ctx, |ui| {
+                        state.simulation.history().show(
+                            ui,
+                            state.simulation.business().catalog(),
+                            &mut state.chart_metric,
+                            &mut state.chart_products,
+                            Simulation::DAY_LENGTH,
+                        );
+                    });
+            }
+
+            if state.window_states[Self::SETTINGS_WINDOW] {
                 Window::new("Settings")
                     .resizable(false)
                     .collapsible(false)
@@ -508,6 +777,37 @@ impl Core {
                         });
                     });
             }
+
+            if state.window_states[Self::SAVE_WINDOW] {
+                Window::new("Save/Load")
+                    .resizable(false)
+                    .collapsible(false)
+                    .show(ctx, |ui| {
+                        ui.columns(2, |uis| {
+                            if uis[0].button("Save").clicked() {
+                                state.save_status = match save::save(&state.simulation) {
+                                    Ok(()) => String::from("Game saved."),
+                                    Err(err) => format!("Failed to save: {}", err),
+                                };
+                            }
+                            if uis[1].button("Load").clicked() {
+                                state.save_status = match save::load() {
+                                    Ok(simulation) => {
+                                        state.seed = simulation.seed();
+                                        state.simulation = simulation;
+                                        state.tick_accumulator = 0.0;
+                                        String::from("Game loaded.")
+                                    }
+                                    Err(err) => format!("Failed to load: {}", err),
+                                };
+                            }
+                        });
+
+                        if !state.save_status.is_empty() {
+                            ui.label(&state.save_status);
+                        }
+                    });
+            }
         });
 
         graphics.render(&ui_output);
@@ -0,0 +1,355 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::bank::Bank;
+use crate::calendar::Season;
+use crate::catalog::{Catalog, ProductId};
+use crate::client::ClassType;
+use crate::expenses::Bill;
+use crate::marketing::CampaignTarget;
+use crate::materials::Material;
+use crate::research::NodeId;
+
+///Everything that can happen in the shop. Kept as data so the UI and
+/// other consumers decide how to filter and word it.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum GameEvent {
+    ClientArrived {
+        class: ClassType,
+    },
+    ClientWants {
+        product: ProductId,
+    },
+    //The client looks at another product after `wanted` did not work out
+    Substitution {
+        wanted: ProductId,
+        product: ProductId,
+    },
+    //The client wants something the catalog does not have
+    NothingToOffer,
+    NotForSale {
+        product: ProductId,
+    },
+    StockOut {
+        product: ProductId,
+    },
+    NoDemand {
+        product: ProductId,
+    },
+    PriceTooHigh {
+        product: ProductId,
+    },
+    ClientSeeks {
+        product: ProductId,
+        units: i32,
+    },
+    NotEnoughStock {
+        product: ProductId,
+    },
+    SaleMade {
+        product: ProductId,
+        units: i32,
+        revenue: i32,
+    },
+    SaleDeclined {
+        product: ProductId,
+    },
+    //Offer per unit from a client who cannot afford the asking price
+    ClientOffers {
+        product: ProductId,
+        offer: i32,
+    },
+    HaggleFailed {
+        product: ProductId,
+    },
+    //A client ran out of patience waiting in line
+    LostSale {
+        class: ClassType,
+        product: Option<ProductId>,
+    },
+    //A client found the line full and left straight away
+    TurnedAway {
+        class: ClassType,
+        product: Option<ProductId>,
+    },
+    //day: the day that just ended, starting at 0
+    DayEnded {
+        day: u32,
+        profit: i32,
+    },
+    SeasonStarted {
+        season: Season,
+    },
+    BillPaid {
+        bill: Bill,
+        amount: i32,
+    },
+    LoanTaken {
+        amount: i32,
+    },
+    LoanRepaid,
+    //amount: installments left unpaid, 0 when funds are past the credit line
+    PaymentMissed {
+        amount: i32,
+        strikes: u32,
+    },
+    Bankrupt,
+    //The client preferred a rival shop's deal
+    BoughtFromRival {
+        product: ProductId,
+        rival: String,
+    },
+    ResearchUnlocked {
+        node: NodeId,
+    },
+    CampaignLaunched {
+        target: CampaignTarget,
+    },
+    CampaignEnded {
+        target: CampaignTarget,
+    },
+    //Production was cut short for lack of storage space
+    WarehouseFull,
+    MaterialsBought {
+        material: Material,
+        units: i32,
+        cost: i32,
+    },
+    //Units thrown away once their batch went bad
+    Spoiled {
+        product: ProductId,
+        units: i32,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    Client,
+    Sale,
+    Research,
+    Marketing,
+    Stock,
+    Finance,
+    Calendar,
+}
+
+impl EventKind {
+    pub const ALL: [EventKind; 7] = [
+        EventKind::Client,
+        EventKind::Sale,
+        EventKind::Research,
+        EventKind::Marketing,
+        EventKind::Stock,
+        EventKind::Finance,
+        EventKind::Calendar,
+    ];
+
+    pub fn name(&self) -> &str {
+        match self {
+            EventKind::Client => "Clients",
+            EventKind::Sale => "Sales",
+            EventKind::Research => "Research",
+            EventKind::Marketing => "Marketing",
+            EventKind::Stock => "Stock",
+            EventKind::Finance => "Finance",
+            EventKind::Calendar => "Calendar",
+        }
+    }
+}
+
+impl GameEvent {
+    pub fn kind(&self) -> EventKind {
+        match self {
+            GameEvent::SaleMade { .. }
+            | GameEvent::SaleDeclined { .. }
+            | GameEvent::ClientOffers { .. }
+            | GameEvent::HaggleFailed { .. }
+            | GameEvent::LostSale { .. }
+            | GameEvent::TurnedAway { .. }
+            | GameEvent::BoughtFromRival { .. } => EventKind::Sale,
+            GameEvent::ResearchUnlocked { .. } => EventKind::Research,
+            GameEvent::CampaignLaunched { .. } | GameEvent::CampaignEnded { .. } => {
+                EventKind::Marketing
+            }
+            GameEvent::Spoiled { .. }
+            | GameEvent::WarehouseFull
+            | GameEvent::MaterialsBought { .. } => EventKind::Stock,
+            GameEvent::BillPaid { .. }
+            | GameEvent::LoanTaken { .. }
+            | GameEvent::LoanRepaid
+            | GameEvent::PaymentMissed { .. }
+            | GameEvent::Bankrupt => EventKind::Finance,
+            GameEvent::DayEnded { .. } | GameEvent::SeasonStarted { .. } => EventKind::Calendar,
+            _ => EventKind::Client,
+        }
+    }
+
+    pub fn describe(&self, catalog: &Catalog) -> String {
+        let name = |product: &ProductId| catalog.name(*product);
+
+        match self {
+            GameEvent::ClientArrived { class } => format!("Client [{}] appears.", class.name()),
+            GameEvent::ClientWants { product } => {
+                format!("Client desires [{}].", name(product))
+            }
+            GameEvent::Substitution { wanted, product } => format!(
+                "Client considers [{}] instead of [{}].",
+                name(product),
+                name(wanted)
+            ),
+            GameEvent::NothingToOffer => {
+                String::from("Client desires something your business does not sell.")
+            }
+            GameEvent::NotForSale { product } => {
+                format!("[{}] is not for sale. (Price is set to 0)", name(product))
+            }
+            GameEvent::StockOut { product } => {
+                format!("You do not have any [{}] available.", name(product))
+            }
+            GameEvent::NoDemand { product } => format!(
+                "Client does not desire anything from your business.\nEither there is not enough demand for [{}], or the price is too high.",
+                name(product)
+            ),
+            GameEvent::PriceTooHigh { product } => format!(
+                "Price of [{}] is more than client is willing to spend.",
+                name(product)
+            ),
+            GameEvent::ClientSeeks { product, units } => format!(
+                "Client seeks to buy {} unit(s) of [{}].",
+                units,
+                name(product)
+            ),
+            GameEvent::NotEnoughStock { .. } => String::from(
+                "Client desires more than is currently available. Client will buy what is remaining.",
+            ),
+            GameEvent::SaleMade { revenue, .. } => {
+                format!("Client has made purchase!\n+${} made!", revenue)
+            }
+            GameEvent::SaleDeclined { .. } => String::from("Client did not make purchase."),
+            GameEvent::ClientOffers { product, offer } => {
+                format!("Client offers ${} for one [{}].", offer, name(product))
+            }
+            GameEvent::HaggleFailed { product } => {
+                format!("Client could not agree on a price for [{}].", name(product))
+            }
+            GameEvent::MaterialsBought {
+                material,
+                units,
+                cost,
+            } => format!("Bought {} {} for ${}.", units, material.name(), cost),
+            GameEvent::DayEnded { day, profit } => {
+                format!("Day {} is over. Net profit: ${}.", day + 1, profit)
+            }
+            GameEvent::SeasonStarted { season } => format!("{} has begun.", season.name()),
+            GameEvent::BillPaid { bill, amount } => {
+                format!("Paid ${} of {}.", amount, bill.name())
+            }
+            GameEvent::LoanTaken { amount } => format!("Borrowed ${} from the bank.", amount),
+            GameEvent::LoanRepaid => String::from("A loan has been paid off."),
+            GameEvent::PaymentMissed { amount, strikes } => {
+                let reason = if *amount > 0 {
+                    format!("Could not pay ${} of loan installments.", amount)
+                } else {
+                    String::from("Funds are past the credit line.")
+                };
+                format!(
+                    "{} Missed payments: {}/{}.",
+                    reason,
+                    strikes,
+                    Bank::MAX_MISSED_PAYMENTS
+                )
+            }
+            GameEvent::Bankrupt => String::from("The bank has declared the shop bankrupt."),
+            GameEvent::BoughtFromRival { product, rival } => {
+                format!("Client bought [{}] from {} instead.", name(product), rival)
+            }
+            GameEvent::LostSale { class, product } => match product {
+                Some(product) => format!(
+                    "Client [{}] got tired of waiting in line and left without buying [{}].",
+                    class.name(),
+                    name(product)
+                ),
+                None => format!(
+                    "Client [{}] got tired of waiting in line and left.",
+                    class.name()
+                ),
+            },
+            GameEvent::TurnedAway { class, product } => match product {
+                Some(product) => format!(
+                    "Client [{}] saw the line was full and left without buying [{}].",
+                    class.name(),
+                    name(product)
+                ),
+                None => format!(
+                    "Client [{}] saw the line was full and left.",
+                    class.name()
+                ),
+            },
+            GameEvent::ResearchUnlocked { node } => format!(
+                "Research & Development completes [{}]. {}",
+                node.get().name,
+                node.get().description
+            ),
+            GameEvent::CampaignLaunched { target } => format!(
+                "Marketing campaign for [{}] launched.",
+                target.name(catalog)
+            ),
+            GameEvent::CampaignEnded { target } => format!(
+                "Marketing campaign for [{}] has run its course.",
+                target.name(catalog)
+            ),
+            GameEvent::WarehouseFull => String::from(
+                "The warehouse is full. Production stops until space frees up.",
+            ),
+            GameEvent::Spoiled { product, units } => format!(
+                "{} unit(s) of [{}] spoiled and were thrown away.",
+                units,
+                name(product)
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LoggedEvent {
+    //Seconds of game time since the run started
+    pub time: f32,
+    pub event: GameEvent,
+}
+
+///Persistent history of game events, oldest first.
+#[derive(Default, Serialize, Deserialize)]
+pub struct EventLog {
+    events: VecDeque<LoggedEvent>,
+    //Timestamp given to new events
+    now: f32,
+}
+
+impl EventLog {
+    //Oldest events are dropped past this to keep saves small
+    const MAX_EVENTS: usize = 5_000;
+
+    pub fn set_time(&mut self, now: f32) {
+        self.now = now;
+    }
+
+    pub fn push(&mut self, event: GameEvent) {
+        if self.events.len() == Self::MAX_EVENTS {
+            self.events.pop_front();
+        }
+
+        self.events.push_back(LoggedEvent {
+            time: self.now,
+            event,
+        });
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &LoggedEvent> {
+        self.events.iter()
+    }
+
+    pub fn since(&self, time: f32) -> impl DoubleEndedIterator<Item = &LoggedEvent> {
+        self.events.iter().filter(move |logged| logged.time >= time)
+    }
+}
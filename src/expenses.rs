@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+
+use crate::calendar::Date;
+use crate::ledger::LedgerCategory;
+
+///Running costs of the shop, charged at the start of a day.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Bill {
+    Rent,
+    Wages,
+    //Collected on revenue as sales are made, paid to the crown weekly
+    SalesTax,
+    //Charged on the value of the stock in the warehouse
+    PropertyTax,
+}
+
+impl Bill {
+    pub const ALL: [Bill; 4] = [Bill::Rent, Bill::Wages, Bill::SalesTax, Bill::PropertyTax];
+
+    pub const RENT: i32 = 500;
+    pub const WAGES: i32 = 100;
+    pub const SALES_TAX_RATE: f32 = 0.1;
+    pub const PROPERTY_TAX_RATE: f32 = 0.02;
+
+    pub fn name(&self) -> &str {
+        match self {
+            Bill::Rent => "Rent",
+            Bill::Wages => "Wages",
+            Bill::SalesTax => "Sales Tax",
+            Bill::PropertyTax => "Property Tax",
+        }
+    }
+
+    //Days between each payment
+    pub fn period(&self) -> u32 {
+        match self {
+            Bill::Wages => 1,
+            _ => Date::DAYS_PER_WEEK,
+        }
+    }
+
+    pub fn category(&self) -> LedgerCategory {
+        match self {
+            Bill::Rent => LedgerCategory::Rent,
+            Bill::Wages => LedgerCategory::Wages,
+            Bill::SalesTax | Bill::PropertyTax => LedgerCategory::Taxes,
+        }
+    }
+
+    //True when the bill is charged as `day` starts
+    pub fn is_due(&self, day: u32) -> bool {
+        day > 0 && day.is_multiple_of(self.period())
+    }
+
+    //Days from `day` until the next payment
+    pub fn days_until(&self, day: u32) -> u32 {
+        self.period() - day % self.period()
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct Expenses {
+    //Sales tax collected since it was last paid
+    sales_tax_owed: f32,
+}
+
+impl Expenses {
+    pub fn collect_sales_tax(&mut self, revenue: i32) {
+        self.sales_tax_owed += revenue as f32 * Bill::SALES_TAX_RATE;
+    }
+
+    pub fn sales_tax_owed(&self) -> i32 {
+        self.sales_tax_owed as i32
+    }
+
+    ///Clears the collected sales tax, returning what is due.
+    pub fn settle_sales_tax(&mut self) -> i32 {
+        let owed = self.sales_tax_owed();
+        self.sales_tax_owed -= owed as f32;
+        owed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bills_come_due_on_their_period() {
+        //Nothing is charged before the first day has passed
+        assert!(Bill::ALL.iter().all(|bill| !bill.is_due(0)));
+
+        assert!((1..=7).all(|day| Bill::Wages.is_due(day)));
+        assert!(!Bill::Rent.is_due(6));
+        assert!(Bill::Rent.is_due(7));
+        assert!(Bill::SalesTax.is_due(14));
+
+        assert_eq!(Bill::Rent.days_until(0), 7);
+        assert_eq!(Bill::Rent.days_until(5), 2);
+        assert_eq!(Bill::Rent.days_until(7), 7);
+        assert_eq!(Bill::Wages.days_until(3), 1);
+    }
+
+    #[test]
+    fn sales_tax_carries_over_fractions() {
+        let mut expenses = Expenses::default();
+        expenses.collect_sales_tax(15);
+        expenses.collect_sales_tax(15);
+
+        assert_eq!(expenses.settle_sales_tax(), 3);
+        assert_eq!(expenses.sales_tax_owed(), 0);
+
+        expenses.collect_sales_tax(5);
+        expenses.collect_sales_tax(5);
+        assert_eq!(expenses.settle_sales_tax(), 1);
+    }
+}
@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+
+use crate::catalog::ProductId;
+
+pub enum HaggleReply {
+    //The client takes the player's price
+    Accept(i32),
+    //The client raises their offer
+    Offer(i32),
+    WalkAway,
+}
+
+///A client bargaining over one unit they cannot afford at the asking price.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Haggle {
+    product: ProductId,
+    asking_price: i32,
+    offer: i32,
+    //Most the client will pay, kept hidden from the player
+    ceiling: i32,
+    //Budget the client set aside for this item
+    budget: i32,
+    counters_left: u32,
+}
+
+impl Haggle {
+    //Counter-offers the player gets before the client walks away
+    pub const MAX_COUNTERS: u32 = 3;
+
+    //Demand at which the client stretches halfway to their full stretch
+    const DEMAND_HALF_STRETCH: f32 = 20.0;
+
+    pub fn open(
+        product: ProductId,
+        asking_price: i32,
+        budget: i32,
+        demand: i32,
+        price_factor: f32,
+    ) -> Self {
+        //Clients who care less about price and want the product more
+        // will go further over their budget
+        let demand = demand.max(0);
+        let stretch = 1.0
+            + (1.0 - price_factor) * demand as f32 / (demand as f32 + Self::DEMAND_HALF_STRETCH);
+        let ceiling = ((budget as f32 * stretch) as i32).min(asking_price);
+
+        let offer = (ceiling as f32 * (1.0 - price_factor / 2.0)) as i32;
+        let offer = offer.clamp(1, (asking_price - 1).max(1));
+
+        Self {
+            product,
+            asking_price,
+            offer,
+            ceiling: ceiling.max(offer),
+            budget,
+            counters_left: Self::MAX_COUNTERS,
+        }
+    }
+
+    pub fn counter(&mut self, price: i32) -> HaggleReply {
+        if price <= self.ceiling {
+            return HaggleReply::Accept(price);
+        }
+
+        self.counters_left = self.counters_left.saturating_sub(1);
+        if self.counters_left == 0 {
+            return HaggleReply::WalkAway;
+        }
+
+        //Meet the player halfway to the most the client will pay
+        self.offer = (self.offer + (self.ceiling - self.offer + 1) / 2).min(self.ceiling);
+        HaggleReply::Offer(self.offer)
+    }
+
+    pub fn product(&self) -> ProductId {
+        self.product
+    }
+
+    pub fn asking_price(&self) -> i32 {
+        self.asking_price
+    }
+
+    pub fn offer(&self) -> i32 {
+        self.offer
+    }
+
+    pub fn budget(&self) -> i32 {
+        self.budget
+    }
+
+    pub fn counters_left(&self) -> u32 {
+        self.counters_left
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::Catalog;
+
+    //$100 budget for a $200 product: the client goes up to $125
+    fn haggle() -> Haggle {
+        let product = Catalog::default().ids().next().unwrap();
+        Haggle::open(product, 200, 100, 20, 0.5)
+    }
+
+    #[test]
+    fn opens_below_the_budget_stretch() {
+        let haggle = haggle();
+        assert_eq!(haggle.offer(), 93);
+        assert_eq!(haggle.budget(), 100);
+        assert_eq!(haggle.counters_left(), Haggle::MAX_COUNTERS);
+
+        //Without demand the client will not go over budget
+        let product = haggle.product();
+        let stingy = Haggle::open(product, 200, 100, 0, 0.5);
+        assert!(matches!(
+            stingy.clone().counter(100),
+            HaggleReply::Accept(100)
+        ));
+        assert!(matches!(stingy.clone().counter(101), HaggleReply::Offer(_)));
+    }
+
+    #[test]
+    fn accepts_up_to_the_ceiling() {
+        assert!(matches!(haggle().counter(125), HaggleReply::Accept(125)));
+        assert!(matches!(haggle().counter(80), HaggleReply::Accept(80)));
+    }
+
+    #[test]
+    fn raises_the_offer_then_walks_away() {
+        let mut haggle = haggle();
+
+        assert!(matches!(haggle.counter(200), HaggleReply::Offer(109)));
+        assert!(matches!(haggle.counter(200), HaggleReply::Offer(117)));
+        assert!(matches!(haggle.counter(200), HaggleReply::WalkAway));
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::VecDeque;
+
+use egui_plot::{Legend, Line, Plot, PlotPoints};
+use notan_egui::*;
+use serde::{Deserialize, Serialize};
+
+use crate::business::Business;
+use crate::catalog::Catalog;
+use crate::simulation::Simulation;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ChartMetric {
+    Funds,
+    Demand,
+    Stock,
+    Price,
+    Sales,
+}
+
+impl ChartMetric {
+    pub const ALL: [ChartMetric; 5] = [
+        ChartMetric::Funds,
+        ChartMetric::Demand,
+        ChartMetric::Stock,
+        ChartMetric::Price,
+        ChartMetric::Sales,
+    ];
+
+    pub fn name(&self) -> &str {
+        match self {
+            ChartMetric::Funds => "Funds",
+            ChartMetric::Demand => "Demand",
+            ChartMetric::Stock => "Stock",
+            ChartMetric::Price => "Price",
+            ChartMetric::Sales => "Sales",
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ProductSample {
+    pub demand: i32,
+    pub stock: i32,
+    pub price: i32,
+    //Units sold since the previous sample
+    pub sales: i32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Sample {
+    //Seconds of game time since the run started
+    pub time: f32,
+    pub funds: i32,
+    //Indexed by ProductId
+    pub products: Vec<ProductSample>,
+}
+
+///Snapshots of the business over time, oldest first, for the Charts window.
+#[derive(Default, Serialize, Deserialize)]
+pub struct History {
+    samples: VecDeque<Sample>,
+    //Units sold per product when the last sample was taken
+    units_sold: Vec<i32>,
+}
+
+impl History {
+    //Ticks between samples, one second of game time
+    pub const SAMPLE_INTERVAL: u64 = Simulation::TICKS_PER_SECOND;
+
+    //An hour of samples; oldest ones are dropped past this to keep saves small
+    const MAX_SAMPLES: usize = 3_600;
+
+    ///Called every tick; takes a sample every `SAMPLE_INTERVAL` ticks.
+    pub fn record(&mut self, tick: u64, now: f32, business: &Business) {
+        if !tick.is_multiple_of(Self::SAMPLE_INTERVAL) {
+            return;
+        }
+
+        self.units_sold.resize(business.catalog().len(), 0);
+
+        let products = business
+            .product_ids()
+            .zip(self.units_sold.iter_mut())
+            .map(|(id, sold)| {
+                let total_sold = business.get_units_sold(id);
+                let sample = ProductSample {
+                    demand: business.get_demand(id),
+                    stock: business.get_quantity(id),
+                    price: business.get_price(id),
+                    sales: total_sold - *sold,
+                };
+                *sold = total_sold;
+                sample
+            })
+            .collect();
+
+        if self.samples.len() == Self::MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+
+        self.samples.push_back(Sample {
+            time: now,
+            funds: business.funds(),
+            products,
+        });
+    }
+
+    pub fn samples(&self) -> impl DoubleEndedIterator<Item = &Sample> {
+        self.samples.iter()
+    }
+
+    fn line(&self, name: &str, day_length: f32, value: impl Fn(&Sample) -> i32) -> Line {
+        //Day 1 starts at x = 1, matching the Finances window
+        let points: PlotPoints = self
+            .samples
+            .iter()
+            .map(|sample| {
+                [
+                    (sample.time / day_length) as f64 + 1.0,
+                    value(sample) as f64,
+                ]
+            })
+            .collect();
+
+        Line::new(points).name(name)
+    }
+
+    //shown: which products are plotted, indexed by ProductId
+    pub fn show(
+        &self,
+        ui: &mut Ui,
+        catalog: &Catalog,
+        metric: &mut ChartMetric,
+        shown: &mut Vec<bool>,
+        day_length: f32,
+    ) {
+        shown.resize(catalog.len(), true);
+
+        ui.horizontal(|ui| {
+            for option in ChartMetric::ALL {
+                ui.selectable_value(metric, option, option.name());
+            }
+        });
+
+        if *metric != ChartMetric::Funds {
+            ui.horizontal_wrapped(|ui| {
+                for id in catalog.ids() {
+                    ui.toggle_value(&mut shown[id.index()], catalog.name(id));
+                }
+            });
+        }
+
+        ui.separator();
+
+        let metric = *metric;
+        Plot::new("history_chart")
+            .height(240.0)
+            .width(480.0)
+            .legend(Legend::default())
+            .x_axis_label("Day")
+            .include_y(0.0)
+            .show(ui, |plot_ui| {
+                if metric == ChartMetric::Funds {
+                    plot_ui.line(self.line("Funds", day_length, |sample| sample.funds));
+                    return;
+                }
+
+                for id in catalog.ids().filter(|id| shown[id.index()]) {
+                    let value = move |sample: &Sample| {
+                        let product = &sample.products[id.index()];
+                        match metric {
+                            ChartMetric::Demand => product.demand,
+                            ChartMetric::Stock => product.stock,
+                            ChartMetric::Price => product.price,
+                            ChartMetric::Sales => product.sales,
+                            ChartMetric::Funds => 0,
+                        }
+                    };
+                    plot_ui.line(self.line(catalog.name(id), day_length, value));
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_once_a_second() {
+        let business = Business::new(10_000, Catalog::default());
+        let mut history = History::default();
+
+        for tick in 1..=3 * Simulation::TICKS_PER_SECOND {
+            let now = tick as f32 / Simulation::TICKS_PER_SECOND as f32;
+            history.record(tick, now, &business);
+        }
+
+        let times: Vec<f32> = history.samples().map(|sample| sample.time).collect();
+        assert_eq!(times, [1.0, 2.0, 3.0]);
+    }
+}
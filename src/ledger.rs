@@ -0,0 +1,276 @@
+use notan_egui::*;
+use serde::{Deserialize, Serialize};
+
+use crate::catalog::ProductId;
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LedgerCategory {
+    Revenue,
+    CostOfGoods,
+    //Spoiled stock written off at what it cost to make. It was paid for
+    // as cost of goods, so each write-off moves that cost over to here.
+    Spoilage,
+    Research,
+    Marketing,
+    //Holding costs for stock in the warehouse
+    Storage,
+    //Warehouse expansions
+    Upgrades,
+    Rent,
+    Wages,
+    //Sales and property taxes
+    Taxes,
+    //Interest on loans and overdrawn funds
+    Interest,
+    //Money borrowed and principal paid back, which is not profit or loss
+    Loans,
+    //Funds set from outside the economy, like picking a difficulty
+    Capital,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    //Seconds of game time since the run started
+    pub time: f32,
+    pub category: LedgerCategory,
+    pub product: Option<ProductId>,
+    //Positive when money comes in, negative when it goes out
+    pub amount: i32,
+}
+
+#[derive(Default, Clone, Copy, Serialize, Deserialize)]
+pub struct IncomeStatement {
+    pub revenue: i32,
+    pub cost_of_goods: i32,
+    pub spoilage: i32,
+    pub research: i32,
+    pub marketing: i32,
+    pub storage: i32,
+    pub upgrades: i32,
+    pub rent: i32,
+    pub wages: i32,
+    pub taxes: i32,
+    pub interest: i32,
+}
+
+impl IncomeStatement {
+    pub fn net_profit(&self) -> i32 {
+        self.revenue
+            - self.cost_of_goods
+            - self.spoilage
+            - self.research
+            - self.marketing
+            - self.storage
+            - self.upgrades
+            - self.rent
+            - self.wages
+            - self.taxes
+            - self.interest
+    }
+
+    fn add(&mut self, entry: &LedgerEntry) {
+        match entry.category {
+            LedgerCategory::Revenue => self.revenue += entry.amount,
+            LedgerCategory::CostOfGoods => self.cost_of_goods -= entry.amount,
+            LedgerCategory::Spoilage => self.spoilage -= entry.amount,
+            LedgerCategory::Research => self.research -= entry.amount,
+            LedgerCategory::Marketing => self.marketing -= entry.amount,
+            LedgerCategory::Storage => self.storage -= entry.amount,
+            LedgerCategory::Upgrades => self.upgrades -= entry.amount,
+            LedgerCategory::Rent => self.rent -= entry.amount,
+            LedgerCategory::Wages => self.wages -= entry.amount,
+            LedgerCategory::Taxes => self.taxes -= entry.amount,
+            LedgerCategory::Interest => self.interest -= entry.amount,
+            LedgerCategory::Loans | LedgerCategory::Capital => (),
+        }
+    }
+
+    fn merge(&mut self, other: &IncomeStatement) {
+        self.revenue += other.revenue;
+        self.cost_of_goods += other.cost_of_goods;
+        self.spoilage += other.spoilage;
+        self.research += other.research;
+        self.marketing += other.marketing;
+        self.storage += other.storage;
+        self.upgrades += other.upgrades;
+        self.rent += other.rent;
+        self.wages += other.wages;
+        self.taxes += other.taxes;
+        self.interest += other.interest;
+    }
+}
+
+///Record of every change to the business' funds. Entries are kept for
+/// the current day; finished days are rolled into one statement each so
+/// the ledger does not grow with every sale.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Ledger {
+    //Entries of days that have not been closed yet
+    entries: Vec<LedgerEntry>,
+    //Indexed by day, starting at 0
+    closed_days: Vec<IncomeStatement>,
+    //Timestamp given to new entries
+    now: f32,
+}
+
+impl Ledger {
+    pub fn set_time(&mut self, now: f32) {
+        self.now = now;
+    }
+
+    pub fn record(&mut self, category: LedgerCategory, product: Option<ProductId>, amount: i32) {
+        if amount == 0 {
+            return;
+        }
+
+        self.entries.push(LedgerEntry {
+            time: self.now,
+            category,
+            product,
+            amount,
+        });
+    }
+
+    pub fn entries(&self) -> &[LedgerEntry] {
+        &self.entries
+    }
+
+    pub fn statement(&self) -> IncomeStatement {
+        let mut statement = IncomeStatement::default();
+        self.closed_days.iter().for_each(|day| statement.merge(day));
+        self.entries.iter().for_each(|entry| statement.add(entry));
+        statement
+    }
+
+    //day starts at 0
+    pub fn day_statement(&self, day: u32, day_length: f32) -> IncomeStatement {
+        if let Some(closed) = self.closed_days.get(day as usize) {
+            return *closed;
+        }
+
+        let mut statement = IncomeStatement::default();
+        self.entries
+            .iter()
+            .filter(|entry| (entry.time / day_length) as u32 == day)
+            .for_each(|entry| statement.add(entry));
+        statement
+    }
+
+    ///Rolls the entries of every day up to `day` into their statements.
+    pub fn close_day(&mut self, day: u32, day_length: f32) {
+        while self.closed_days.len() <= day as usize {
+            let statement = self.day_statement(self.closed_days.len() as u32, day_length);
+            self.closed_days.push(statement);
+        }
+
+        self.entries
+            .retain(|entry| (entry.time / day_length) as u32 > day);
+    }
+
+    //selected_day: day shown next to the whole run, starting at 0
+    pub fn show(&self, ui: &mut Ui, selected_day: &mut u32, current_day: u32, day_length: f32) {
+        *selected_day = (*selected_day).min(current_day);
+
+        ui.horizontal(|ui| {
+            ui.label("Day:");
+            ui.add(
+                DragValue::new(selected_day)
+                    .clamp_range(0..=current_day)
+                    .custom_formatter(|day, _| format!("{}", day as u32 + 1))
+                    .custom_parser(|text| text.parse::<f64>().ok().map(|day| day - 1.0)),
+            );
+            if ui.button("Today").clicked() {
+                *selected_day = current_day;
+            }
+        });
+
+        ui.separator();
+
+        let day = self.day_statement(*selected_day, day_length);
+        let run = self.statement();
+
+        let rows = [
+            ("Revenue", day.revenue, run.revenue),
+            ("Cost of Goods", -day.cost_of_goods, -run.cost_of_goods),
+            ("Spoilage", -day.spoilage, -run.spoilage),
+            ("Research & Development", -day.research, -run.research),
+            ("Marketing", -day.marketing, -run.marketing),
+            ("Storage", -day.storage, -run.storage),
+            ("Upgrades", -day.upgrades, -run.upgrades),
+            ("Rent", -day.rent, -run.rent),
+            ("Wages", -day.wages, -run.wages),
+            ("Taxes", -day.taxes, -run.taxes),
+            ("Interest", -day.interest, -run.interest),
+        ];
+
+        Grid::new("income_statement")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("");
+                ui.strong(format!("Day {}", *selected_day + 1));
+                ui.strong("Whole Run");
+                ui.end_row();
+
+                for (name, day_amount, run_amount) in rows {
+                    ui.label(name);
+                    ui.label(format!("${}", day_amount));
+                    ui.label(format!("${}", run_amount));
+                    ui.end_row();
+                }
+
+                ui.strong("Net Profit");
+                ui.strong(format!("${}", day.net_profit()));
+                ui.strong(format!("${}", run.net_profit()));
+                ui.end_row();
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY_LENGTH: f32 = 60.0;
+
+    //Two days of trading: $100 of sales on day 0, $30 of costs on each day
+    fn ledger() -> Ledger {
+        let mut ledger = Ledger::default();
+        ledger.record(LedgerCategory::Capital, None, 1_000);
+        ledger.set_time(10.0);
+        ledger.record(LedgerCategory::Revenue, None, 100);
+        ledger.record(LedgerCategory::CostOfGoods, None, -30);
+        ledger.record(LedgerCategory::Loans, None, 500);
+        ledger.set_time(70.0);
+        ledger.record(LedgerCategory::Rent, None, -30);
+        ledger
+    }
+
+    #[test]
+    fn statements_split_by_day() {
+        let ledger = ledger();
+
+        let first = ledger.day_statement(0, DAY_LENGTH);
+        assert_eq!(first.revenue, 100);
+        assert_eq!(first.cost_of_goods, 30);
+        //Capital and loans are not profit
+        assert_eq!(first.net_profit(), 70);
+
+        let second = ledger.day_statement(1, DAY_LENGTH);
+        assert_eq!(second.rent, 30);
+        assert_eq!(second.net_profit(), -30);
+
+        assert_eq!(ledger.statement().net_profit(), 40);
+    }
+
+    #[test]
+    fn closed_days_keep_their_statements() {
+        let mut ledger = ledger();
+        ledger.close_day(0, DAY_LENGTH);
+
+        assert_eq!(ledger.entries().len(), 1);
+        assert_eq!(ledger.day_statement(0, DAY_LENGTH).net_profit(), 70);
+        assert_eq!(ledger.day_statement(1, DAY_LENGTH).net_profit(), -30);
+        assert_eq!(ledger.statement().net_profit(), 40);
+    }
+}
@@ -1,7 +1,24 @@
-mod business;
-mod client;
-mod columns_ui;
+
+pub mod bank;
+pub mod business;
+pub mod calendar;
+pub mod catalog;
+pub mod client;
+pub mod columns_ui;
 mod core;
+pub mod events;
+pub mod expenses;
+pub mod haggle;
+pub mod history;
+pub mod ledger;
+pub mod market;
+pub mod marketing;
+pub mod materials;
+pub mod reputation;
+pub mod research;
+pub mod save;
+pub mod simulation;
+pub mod warehouse;
 
 use notan::draw::DrawConfig;
 use notan::prelude::*;
@@ -21,6 +38,7 @@ fn main() -> Result<(), String> {
         })
         .add_config(DrawConfig)
         .add_config(EguiConfig)
+        .add_loader(core::Core::create_text_loader())
         .add_config(notan::log::LogConfig::debug())
         .update(core::Core::update)
         .draw(core::Core::draw)
@@ -0,0 +1,270 @@
+use notan_egui::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::business::{Business, Quality};
+use crate::catalog::ProductId;
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PricingStrategy {
+    //Prices just below ours
+    Undercut,
+    //Better goods at a high markup
+    Premium,
+    //A fixed markup on cost that drifts a little each day
+    Steady,
+}
+
+///A competing shop. Rivals never run out of stock; they only compete
+/// on price and quality.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Rival {
+    pub name: String,
+    pub strategy: PricingStrategy,
+    //Indexed by ProductId
+    prices: Vec<i32>,
+}
+
+impl Rival {
+    fn new(name: &str, strategy: PricingStrategy, business: &Business) -> Self {
+        let mut rival = Self {
+            name: String::from(name),
+            strategy,
+            prices: vec![0; business.catalog().len()],
+        };
+
+        for id in business.product_ids() {
+            rival.prices[id.index()] = rival.markup_price(id, business, 1.0);
+        }
+        rival
+    }
+
+    pub fn price(&self, id: ProductId) -> i32 {
+        self.prices[id.index()]
+    }
+
+    pub fn quality(&self) -> Quality {
+        match self.strategy {
+            PricingStrategy::Premium => Quality::Good,
+            _ => Quality::Basic,
+        }
+    }
+
+    //drift: multiplier applied on top of the strategy's markup
+    fn markup_price(&self, id: ProductId, business: &Business, drift: f32) -> i32 {
+        let markup = match self.strategy {
+            PricingStrategy::Undercut => 2.0,
+            PricingStrategy::Premium => 3.5,
+            PricingStrategy::Steady => 2.5,
+        };
+
+        (business.catalog().get(id).standard_cost() as f32 * markup * drift) as i32
+    }
+
+    fn reprice(&mut self, business: &Business, rng: &mut impl Rng) {
+        for id in business.product_ids() {
+            let cost = business.catalog().get(id).standard_cost();
+
+            let price = match self.strategy {
+                PricingStrategy::Undercut if business.get_price(id) > 0 => {
+                    (business.get_price(id) as f32 * 0.9) as i32
+                }
+                PricingStrategy::Steady => {
+                    self.markup_price(id, business, rng.gen_range(0.9..=1.1))
+                }
+                _ => self.markup_price(id, business, 1.0),
+            };
+
+            //Rivals never sell at a loss
+            self.prices[id.index()] = price.max(cost + 1);
+        }
+    }
+}
+
+///Competing shops and how many clients each product has won or lost to them.
+#[derive(Serialize, Deserialize)]
+pub struct Market {
+    rivals: Vec<Rival>,
+    //Indexed by ProductId, counted in clients
+    won: Vec<i32>,
+    lost: Vec<i32>,
+}
+
+impl Market {
+    //How strongly clients prefer the better deal. Higher is more random.
+    const CHOICE_SPREAD: f32 = 5.0;
+
+    pub fn new(business: &Business) -> Self {
+        let products = business.catalog().len();
+
+        Self {
+            rivals: vec![
+                Rival::new("Grimm's Goods", PricingStrategy::Undercut, business),
+                Rival::new("The Gilded Gauntlet", PricingStrategy::Premium, business),
+                Rival::new("Old Tom's Stall", PricingStrategy::Steady, business),
+            ],
+            won: vec![0; products],
+            lost: vec![0; products],
+        }
+    }
+
+    pub fn rivals(&self) -> &[Rival] {
+        &self.rivals
+    }
+
+    ///Rivals react to the market once a day.
+    pub fn reprice(&mut self, business: &Business, rng: &mut impl Rng) {
+        for rival in self.rivals.iter_mut() {
+            rival.reprice(business, rng);
+        }
+    }
+
+    ///Picks where a client buys `id`: None for our shop, otherwise the
+    /// index of a rival. Clients weigh price against quality and only
+    /// consider shops within their budget.
+    //ours: None when we cannot sell the product right now
+    pub fn choose(
+        &self,
+        id: ProductId,
+        ours: Option<(i32, Quality)>,
+        budget: i32,
+        price_factor: f32,
+        quality_factor: f32,
+        rng: &mut impl Rng,
+    ) -> Option<usize> {
+        let score = |price: i32, quality: Quality| {
+            ((quality_factor * quality.value() - price_factor * price as f32) / Self::CHOICE_SPREAD)
+                .exp()
+        };
+
+        let our_score = ours.map_or(0.0, |(price, quality)| score(price, quality));
+        let rival_scores: Vec<f32> = self
+            .rivals
+            .iter()
+            .map(|rival| {
+                if rival.price(id) <= budget {
+                    score(rival.price(id), rival.quality())
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+
+        let total = our_score + rival_scores.iter().sum::<f32>();
+        if total <= 0.0 {
+            return None;
+        }
+
+        let mut pick = rng.gen_range(0.0..total) - our_score;
+        if pick < 0.0 {
+            return None;
+        }
+
+        for (index, rival_score) in rival_scores.into_iter().enumerate() {
+            pick -= rival_score;
+            if pick < 0.0 && rival_score > 0.0 {
+                return Some(index);
+            }
+        }
+
+        None
+    }
+
+    pub fn record(&mut self, id: ProductId, rival: Option<usize>) {
+        match rival {
+            None => self.won[id.index()] += 1,
+            Some(_) => self.lost[id.index()] += 1,
+        }
+    }
+
+    //Share of clients for `id` that bought from us, None before anyone has
+    pub fn share(&self, id: ProductId) -> Option<f32> {
+        let won = self.won[id.index()];
+        let total = won + self.lost[id.index()];
+        (total > 0).then(|| won as f32 / total as f32)
+    }
+
+    pub fn show(&self, ui: &mut Ui, business: &Business) {
+        let catalog = business.catalog();
+
+        Grid::new("market")
+            .num_columns(self.rivals.len() + 3)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("");
+                ui.strong("You");
+                for rival in self.rivals.iter() {
+                    ui.strong(&rival.name).on_hover_text(match rival.strategy {
+                        PricingStrategy::Undercut => "Prices just below yours.",
+                        PricingStrategy::Premium => "Sells good quality at a premium.",
+                        PricingStrategy::Steady => "Keeps prices close to a fixed markup.",
+                    });
+                }
+                ui.strong("Your Share");
+                ui.end_row();
+
+                for id in catalog.ids() {
+                    ui.label(catalog.name(id));
+                    ui.label(format!("${}", business.get_price(id)));
+                    for rival in self.rivals.iter() {
+                        ui.label(format!("${}", rival.price(id)));
+                    }
+                    ui.label(match self.share(id) {
+                        Some(share) => format!("{:.0}%", share * 100.0),
+                        None => String::from("-"),
+                    });
+                    ui.end_row();
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha12Rng;
+
+    use super::*;
+    use crate::catalog::Catalog;
+
+    fn market() -> (Market, ProductId) {
+        let business = Business::new(10_000, Catalog::default());
+        let id = business.product_ids().next().unwrap();
+        (Market::new(&business), id)
+    }
+
+    #[test]
+    fn rival_wins_when_we_cannot_sell() {
+        let (market, id) = market();
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+
+        for _ in 0..100 {
+            assert!(market.choose(id, None, 1_000, 0.5, 0.4, &mut rng).is_some());
+        }
+        //Nobody is within a budget of $1
+        assert_eq!(market.choose(id, None, 1, 0.5, 0.4, &mut rng), None);
+    }
+
+    #[test]
+    fn we_win_when_rivals_are_over_budget() {
+        let (market, id) = market();
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+
+        for _ in 0..100 {
+            let ours = Some((1, Quality::Basic));
+            assert_eq!(market.choose(id, ours, 1, 0.5, 0.4, &mut rng), None);
+        }
+    }
+
+    #[test]
+    fn both_shops_win_some_clients() {
+        let (market, id) = market();
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+        let ours = Some((market.rivals[0].price(id), Quality::Basic));
+
+        let won = (0..1_000)
+            .filter(|_| market.choose(id, ours, 1_000, 0.5, 0.4, &mut rng).is_none())
+            .count();
+        assert!(won > 0 && won < 1_000);
+    }
+}
@@ -0,0 +1,254 @@
+use notan_egui::*;
+use serde::{Deserialize, Serialize};
+
+use crate::catalog::{Catalog, ProductId};
+use crate::client::ClassType;
+use crate::simulation::Simulation;
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CampaignTarget {
+    Shop,
+    Class(ClassType),
+    Product(ProductId),
+}
+
+impl CampaignTarget {
+    pub const CLASSES: [ClassType; 3] = [ClassType::Fighter, ClassType::Cleric, ClassType::Mage];
+
+    pub fn name(&self, catalog: &Catalog) -> String {
+        match self {
+            CampaignTarget::Shop => String::from("Whole Shop"),
+            CampaignTarget::Class(class) => format!("{}s", class.name()),
+            CampaignTarget::Product(id) => catalog.name(*id).to_string(),
+        }
+    }
+
+    //Share of a campaign's demand boost that reaches each targeted product,
+    // since broad campaigns spread their budget thinner
+    fn reach(&self) -> f32 {
+        match self {
+            CampaignTarget::Shop => 0.2,
+            CampaignTarget::Class(_) => 0.5,
+            CampaignTarget::Product(_) => 1.0,
+        }
+    }
+
+    fn includes(&self, id: ProductId, catalog: &Catalog) -> bool {
+        match self {
+            CampaignTarget::Shop => true,
+            CampaignTarget::Class(class) => catalog.get(id).class == Some(*class),
+            CampaignTarget::Product(target) => *target == id,
+        }
+    }
+}
+
+///Paid advertising whose effect starts at full strength and fades to
+/// nothing over its duration.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Campaign {
+    pub target: CampaignTarget,
+    pub budget: i32,
+    //Seconds of game time
+    pub started: f32,
+    pub duration: f32,
+}
+
+impl Campaign {
+    //Dollars per day of campaign needed for one extra point of demand
+    const DOLLARS_PER_DEMAND: f32 = 25.0;
+
+    //Dollars per day of campaign needed to double how often a class visits
+    const DOLLARS_PER_SPAWN_WEIGHT: f32 = 1_000.0;
+
+    //1 when launched, down to 0 when over
+    fn strength(&self, now: f32) -> f32 {
+        (1.0 - (now - self.started) / self.duration).clamp(0.0, 1.0)
+    }
+
+    fn daily_budget(&self) -> f32 {
+        self.budget as f32 / (self.duration / Simulation::DAY_LENGTH)
+    }
+
+    pub fn is_over(&self, now: f32) -> bool {
+        now - self.started >= self.duration
+    }
+}
+
+///Draft of a campaign the player is about to launch.
+pub struct CampaignDraft {
+    pub target: CampaignTarget,
+    pub budget: i32,
+    pub days: u32,
+}
+
+impl Default for CampaignDraft {
+    fn default() -> Self {
+        Self {
+            target: CampaignTarget::Shop,
+            budget: 1_000,
+            days: 1,
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct Marketing {
+    campaigns: Vec<Campaign>,
+}
+
+impl Marketing {
+    pub fn launch(&mut self, campaign: Campaign) {
+        self.campaigns.push(campaign);
+    }
+
+    ///Removes and returns campaigns that have run their course.
+    pub fn expire(&mut self, now: f32) -> Vec<Campaign> {
+        let (over, running) = self
+            .campaigns
+            .drain(..)
+            .partition(|campaign| campaign.is_over(now));
+        self.campaigns = running;
+        over
+    }
+
+    pub fn campaigns(&self) -> &[Campaign] {
+        &self.campaigns
+    }
+
+    pub fn demand_boost(&self, id: ProductId, catalog: &Catalog, now: f32) -> f32 {
+        self.campaigns
+            .iter()
+            .filter(|campaign| campaign.target.includes(id, catalog))
+            .map(|campaign| {
+                campaign.daily_budget() / Campaign::DOLLARS_PER_DEMAND
+                    * campaign.target.reach()
+                    * campaign.strength(now)
+            })
+            .sum()
+    }
+
+    //Multiplier on how likely a client of `class` is to walk in
+    pub fn spawn_weight(&self, class: ClassType, now: f32) -> f32 {
+        1.0 + self
+            .campaigns
+            .iter()
+            .filter(|campaign| campaign.target == CampaignTarget::Class(class))
+            .map(|campaign| {
+                campaign.daily_budget() / Campaign::DOLLARS_PER_SPAWN_WEIGHT
+                    * campaign.strength(now)
+            })
+            .sum::<f32>()
+    }
+
+    //True when the player presses Launch
+    pub fn show(
+        &self,
+        ui: &mut Ui,
+        draft: &mut CampaignDraft,
+        catalog: &Catalog,
+        now: f32,
+    ) -> bool {
+        if self.campaigns.is_empty() {
+            ui.label("No campaigns running.");
+        }
+
+        Grid::new("campaigns")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                for campaign in self.campaigns.iter() {
+                    ui.label(campaign.target.name(catalog));
+                    ui.label(format!("${}", campaign.budget));
+                    ui.add(
+                        ProgressBar::new(campaign.strength(now))
+                            .desired_width(120.0)
+                            .text(format!("{:.0}%", campaign.strength(now) * 100.0)),
+                    )
+                    .on_hover_text("Strength left before the campaign fades out.");
+                    ui.end_row();
+                }
+            });
+
+        ui.separator();
+
+        ComboBox::from_label("Target")
+            .selected_text(draft.target.name(catalog))
+            .show_ui(ui, |ui| {
+                let targets = std::iter::once(CampaignTarget::Shop)
+                    .chain(CampaignTarget::CLASSES.map(CampaignTarget::Class))
+                    .chain(catalog.ids().map(CampaignTarget::Product));
+
+                for target in targets {
+                    ui.selectable_value(&mut draft.target, target, target.name(catalog));
+                }
+            });
+
+        ui.add(
+            DragValue::new(&mut draft.budget)
+                .prefix("Budget: $")
+                .speed(50)
+                .clamp_range(100..=1_000_000),
+        );
+        ui.add(
+            DragValue::new(&mut draft.days)
+                .prefix("Days: ")
+                .clamp_range(1..=14),
+        )
+        .on_hover_text("The same budget over more days gives a weaker but longer boost.");
+
+        ui.button("Launch").clicked()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: f32 = Simulation::DAY_LENGTH;
+
+    fn campaign(target: CampaignTarget, budget: i32, days: f32) -> Campaign {
+        Campaign {
+            target,
+            budget,
+            started: DAY,
+            duration: days * DAY,
+        }
+    }
+
+    #[test]
+    fn campaigns_fade_out_over_their_duration() {
+        let catalog = Catalog::default();
+        let food = catalog.find_by_name("Food").unwrap();
+        let mut marketing = Marketing::default();
+        marketing.launch(campaign(CampaignTarget::Product(food), 1_000, 2.0));
+
+        //$500 a day at $25 per point of demand
+        assert_eq!(marketing.demand_boost(food, &catalog, DAY), 20.0);
+        assert_eq!(marketing.demand_boost(food, &catalog, 2.0 * DAY), 10.0);
+        assert_eq!(marketing.demand_boost(food, &catalog, 3.0 * DAY), 0.0);
+
+        assert!(marketing.expire(2.0 * DAY).is_empty());
+        assert_eq!(marketing.expire(3.0 * DAY).len(), 1);
+        assert!(marketing.campaigns().is_empty());
+    }
+
+    #[test]
+    fn broad_campaigns_spread_thinner() {
+        let catalog = Catalog::default();
+        let armor = catalog.find_by_name("Fighter Armor").unwrap();
+        let food = catalog.find_by_name("Food").unwrap();
+        let mut marketing = Marketing::default();
+        marketing.launch(campaign(CampaignTarget::Shop, 1_000, 1.0));
+        marketing.launch(campaign(
+            CampaignTarget::Class(ClassType::Fighter),
+            1_000,
+            1.0,
+        ));
+
+        assert_eq!(marketing.demand_boost(armor, &catalog, DAY), 8.0 + 20.0);
+        assert_eq!(marketing.demand_boost(food, &catalog, DAY), 8.0);
+
+        assert_eq!(marketing.spawn_weight(ClassType::Fighter, DAY), 2.0);
+        assert_eq!(marketing.spawn_weight(ClassType::Mage, DAY), 1.0);
+    }
+}
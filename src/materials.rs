@@ -0,0 +1,245 @@
+use std::collections::BTreeMap;
+
+use notan_egui::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Material {
+    Iron,
+    Leather,
+    Cloth,
+    Herbs,
+    Grain,
+    #[serde(rename = "Mana Crystal")]
+    ManaCrystal,
+}
+
+impl Material {
+    pub const ALL: [Material; 6] = [
+        Material::Iron,
+        Material::Leather,
+        Material::Cloth,
+        Material::Herbs,
+        Material::Grain,
+        Material::ManaCrystal,
+    ];
+
+    pub fn name(&self) -> &str {
+        match self {
+            Material::Iron => "Iron",
+            Material::Leather => "Leather",
+            Material::Cloth => "Cloth",
+            Material::Herbs => "Herbs",
+            Material::Grain => "Grain",
+            Material::ManaCrystal => "Mana Crystal",
+        }
+    }
+
+    //Price per unit the market drifts around
+    pub fn base_price(&self) -> f32 {
+        match self {
+            Material::Iron => 6.0,
+            Material::Leather => 4.0,
+            Material::Cloth => 3.0,
+            Material::Herbs => 2.0,
+            Material::Grain => 1.0,
+            Material::ManaCrystal => 10.0,
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+///Units of each material that go into one unit of a product.
+pub type Recipe = BTreeMap<Material, i32>;
+
+///Market prices for raw materials and the shop's own stockpile of them.
+#[derive(Serialize, Deserialize)]
+pub struct Materials {
+    //Indexed by Material
+    prices: [f32; Material::ALL.len()],
+    //Prices before the last market move, to show which way they went
+    previous: [f32; Material::ALL.len()],
+    stockpile: [i32; Material::ALL.len()],
+}
+
+impl Default for Materials {
+    fn default() -> Self {
+        let prices = Material::ALL.map(|material| material.base_price());
+
+        Self {
+            prices,
+            previous: prices,
+            stockpile: [0; Material::ALL.len()],
+        }
+    }
+}
+
+impl Materials {
+    //Largest daily move as a share of the base price
+    const VOLATILITY: f32 = 0.2;
+
+    //Share of the gap to the base price closed each day
+    const REVERSION: f32 = 0.2;
+
+    //Prices stay within this factor of the base price
+    const MIN_FACTOR: f32 = 0.5;
+    const MAX_FACTOR: f32 = 2.0;
+
+    pub fn price(&self, material: Material) -> f32 {
+        self.prices[material.index()]
+    }
+
+    pub fn stockpile(&self, material: Material) -> i32 {
+        self.stockpile[material.index()]
+    }
+
+    ///Moves every price randomly, pulled back toward its base price.
+    pub fn update_prices(&mut self, rng: &mut impl Rng) {
+        self.previous = self.prices;
+
+        for material in Material::ALL {
+            let base = material.base_price();
+            let price = &mut self.prices[material.index()];
+
+            *price += (base - *price) * Self::REVERSION
+                + base * rng.gen_range(-Self::VOLATILITY..=Self::VOLATILITY);
+            *price = price.clamp(base * Self::MIN_FACTOR, base * Self::MAX_FACTOR);
+        }
+    }
+
+    //Cost of buying `units` at today's price
+    pub fn cost(&self, material: Material, units: i32) -> i32 {
+        (self.price(material) * units as f32).round() as i32
+    }
+
+    //Cost of the materials for one unit at today's prices
+    pub fn recipe_cost(&self, recipe: &Recipe) -> f32 {
+        recipe
+            .iter()
+            .map(|(material, units)| self.price(*material) * *units as f32)
+            .sum()
+    }
+
+    pub fn add(&mut self, material: Material, units: i32) {
+        self.stockpile[material.index()] += units;
+    }
+
+    ///Uses up the stockpile for `batches` units of `recipe`, returning what
+    /// the rest costs to buy from the market.
+    pub fn consume(&mut self, recipe: &Recipe, batches: i32) -> i32 {
+        recipe
+            .iter()
+            .map(|(material, units)| {
+                let needed = units * batches;
+                let stocked = &mut self.stockpile[material.index()];
+
+                let from_stock = needed.min(*stocked);
+                *stocked -= from_stock;
+                (self.prices[material.index()] * (needed - from_stock) as f32).round() as i32
+            })
+            .sum()
+    }
+
+    //Returns the material and units when the player presses Buy
+    pub fn show(
+        &self,
+        ui: &mut Ui,
+        amounts: &mut [i32; Material::ALL.len()],
+    ) -> Option<(Material, i32)> {
+        let mut bought = None;
+
+        Grid::new("materials")
+            .num_columns(5)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("");
+                ui.strong("Price");
+                ui.strong("Stockpile");
+                ui.label("");
+                ui.label("");
+                ui.end_row();
+
+                for material in Material::ALL {
+                    let price = self.price(material);
+                    let previous = self.previous[material.index()];
+
+                    ui.label(material.name());
+                    let trend = if price > previous {
+                        RichText::new(format!("${:.2} ⬆", price)).color(Color32::RED)
+                    } else if price < previous {
+                        RichText::new(format!("${:.2} ⬇", price)).color(Color32::GREEN)
+                    } else {
+                        RichText::new(format!("${:.2}", price))
+                    };
+                    ui.label(trend)
+                        .on_hover_text(format!("Usually ${:.2}", material.base_price()));
+                    ui.label(format!("{}", self.stockpile(material)));
+
+                    let amount = &mut amounts[material.index()];
+                    ui.add(DragValue::new(amount).clamp_range(1..=1000));
+                    if ui
+                        .button(format!("Buy ${}", self.cost(material, *amount)))
+                        .clicked()
+                    {
+                        bought = Some((material, *amount));
+                    }
+                    ui.end_row();
+                }
+            });
+
+        ui.label("Production uses the stockpile first and buys the rest at market price.");
+
+        bought
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha12Rng;
+
+    use super::*;
+
+    #[test]
+    fn consume_uses_the_stockpile_first() {
+        let mut materials = Materials::default();
+        let recipe = Recipe::from([(Material::Iron, 2), (Material::Leather, 1)]);
+        materials.add(Material::Iron, 5);
+
+        //6 Iron and 3 Leather needed, 1 Iron and all the Leather bought
+        assert_eq!(materials.consume(&recipe, 3), 6 + 3 * 4);
+        assert_eq!(materials.stockpile(Material::Iron), 0);
+        assert_eq!(materials.stockpile(Material::Leather), 0);
+
+        materials.add(Material::Iron, 10);
+        materials.add(Material::Leather, 10);
+        assert_eq!(materials.consume(&recipe, 2), 0);
+        assert_eq!(materials.stockpile(Material::Iron), 6);
+        assert_eq!(materials.stockpile(Material::Leather), 8);
+    }
+
+    #[test]
+    fn prices_stay_near_their_base() {
+        let mut materials = Materials::default();
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+
+        for _ in 0..1_000 {
+            materials.update_prices(&mut rng);
+
+            for material in Material::ALL {
+                let base = material.base_price();
+                let price = materials.price(material);
+                assert!(price >= base * Materials::MIN_FACTOR);
+                assert!(price <= base * Materials::MAX_FACTOR);
+            }
+        }
+
+        assert!(Material::ALL
+            .iter()
+            .any(|&material| materials.price(material) != material.base_price()));
+    }
+}
@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+
+use crate::business::Quality;
+use crate::client::ClassType;
+
+///How a client felt about one item in their basket.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum Satisfaction {
+    Bought(Quality),
+    //Could afford it but chose not to buy
+    Declined,
+    WentToRival,
+    //Too little demand at our price, or the haggle fell through
+    TooExpensive,
+    OutOfStock,
+    //Not for sale, or nothing in the category at all
+    NotSold,
+    //Walked out of the line before being served
+    GaveUp,
+}
+
+impl Satisfaction {
+    //Out of Reputation::MAX_STARS
+    pub fn stars(&self) -> f32 {
+        match self {
+            Satisfaction::Bought(Quality::Basic) => 4.0,
+            Satisfaction::Bought(Quality::Good) => 4.5,
+            Satisfaction::Bought(Quality::Exceptional) => 5.0,
+            Satisfaction::Declined => 3.0,
+            Satisfaction::WentToRival => 2.5,
+            Satisfaction::TooExpensive => 2.0,
+            Satisfaction::OutOfStock => 1.5,
+            Satisfaction::NotSold => 1.5,
+            Satisfaction::GaveUp => 0.5,
+        }
+    }
+}
+
+///Word of mouth about the shop, built up from every visit. Better
+/// reputation brings clients in more often, and each class comes more
+/// often the happier it has been with the shop.
+#[derive(Serialize, Deserialize)]
+pub struct Reputation {
+    stars: f32,
+    //Indexed like Reputation::CLASSES
+    class_stars: [f32; 3],
+}
+
+impl Default for Reputation {
+    fn default() -> Self {
+        Self {
+            stars: Self::STARTING_STARS,
+            class_stars: [Self::STARTING_STARS; 3],
+        }
+    }
+}
+
+impl Reputation {
+    pub const MAX_STARS: f32 = 5.0;
+    const STARTING_STARS: f32 = 3.0;
+
+    //How much a single visit moves the shop's and the class' rating
+    const VISIT_WEIGHT: f32 = 0.05;
+    const CLASS_VISIT_WEIGHT: f32 = 0.1;
+
+    pub const CLASSES: [ClassType; 3] = [ClassType::Fighter, ClassType::Cleric, ClassType::Mage];
+
+    fn class_index(class: ClassType) -> usize {
+        match class {
+            ClassType::Fighter => 0,
+            ClassType::Cleric => 1,
+            ClassType::Mage => 2,
+        }
+    }
+
+    pub fn stars(&self) -> f32 {
+        self.stars
+    }
+
+    pub fn class_stars(&self, class: ClassType) -> f32 {
+        self.class_stars[Self::class_index(class)]
+    }
+
+    ///Rates a finished visit by the average of its items.
+    pub fn record_visit(&mut self, class: ClassType, items: &[Satisfaction]) {
+        if items.is_empty() {
+            return;
+        }
+
+        let rating = items.iter().map(Satisfaction::stars).sum::<f32>() / items.len() as f32;
+
+        self.stars += (rating - self.stars) * Self::VISIT_WEIGHT;
+        let class_stars = &mut self.class_stars[Self::class_index(class)];
+        *class_stars += (rating - *class_stars) * Self::CLASS_VISIT_WEIGHT;
+    }
+
+    //Multiplier on how often clients walk in, from 0.5 at 0 stars to 1.5 at 5
+    pub fn arrival_multiplier(&self) -> f32 {
+        0.5 + self.stars / Self::MAX_STARS
+    }
+
+    //Multiplier on how likely a client of `class` is to walk in
+    pub fn class_weight(&self, class: ClassType) -> f32 {
+        0.5 + self.class_stars(class) / Self::MAX_STARS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn visits_move_the_rating_toward_their_average() {
+        let mut reputation = Reputation::default();
+        let visit = [
+            Satisfaction::Bought(Quality::Exceptional),
+            Satisfaction::OutOfStock,
+        ];
+
+        //Rated 3.25, a quarter star above where the shop starts
+        reputation.record_visit(ClassType::Mage, &visit);
+        assert!(close(reputation.stars(), 3.0 + 0.25 * 0.05));
+        assert!(close(
+            reputation.class_stars(ClassType::Mage),
+            3.0 + 0.25 * 0.1
+        ));
+        assert_eq!(reputation.class_stars(ClassType::Fighter), 3.0);
+
+        //Clients who bought nothing leave no rating
+        let stars = reputation.stars();
+        reputation.record_visit(ClassType::Mage, &[]);
+        assert_eq!(reputation.stars(), stars);
+    }
+
+    #[test]
+    fn unhappy_clients_come_less_often() {
+        let mut reputation = Reputation::default();
+        for _ in 0..200 {
+            reputation.record_visit(ClassType::Fighter, &[Satisfaction::GaveUp]);
+        }
+
+        assert!(close(reputation.stars(), 0.5));
+        assert!(close(reputation.arrival_multiplier(), 0.6));
+        assert!(
+            reputation.class_weight(ClassType::Fighter)
+                < reputation.class_weight(ClassType::Cleric)
+        );
+    }
+}
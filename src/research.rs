@@ -0,0 +1,490 @@
+use notan_egui::*;
+use serde::{Deserialize, Serialize};
+
+use crate::business::Quality;
+use crate::catalog::ProductDef;
+use crate::client::{ClassType, Prioritization};
+
+///Line of products that R&D funding is spent on.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ResearchTrack {
+    //Products every class buys
+    Food,
+    Fighter,
+    Cleric,
+    Mage,
+}
+
+impl ResearchTrack {
+    pub const ALL: [ResearchTrack; 4] = [
+        ResearchTrack::Food,
+        ResearchTrack::Fighter,
+        ResearchTrack::Cleric,
+        ResearchTrack::Mage,
+    ];
+
+    pub fn of(product: &ProductDef) -> Self {
+        match product.class {
+            None => ResearchTrack::Food,
+            Some(ClassType::Fighter) => ResearchTrack::Fighter,
+            Some(ClassType::Cleric) => ResearchTrack::Cleric,
+            Some(ClassType::Mage) => ResearchTrack::Mage,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ResearchTrack::Food => "Food",
+            ResearchTrack::Fighter => "Fighter",
+            ResearchTrack::Cleric => "Cleric",
+            ResearchTrack::Mage => "Mage",
+        }
+    }
+
+    pub fn allocation_name(&self) -> &'static str {
+        match self {
+            ResearchTrack::Food => "Food R&D",
+            ResearchTrack::Fighter => "Fighter R&D",
+            ResearchTrack::Cleric => "Cleric R&D",
+            ResearchTrack::Mage => "Mage R&D",
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum ResearchEffect {
+    //Raises the quality of the track's products in a category
+    Quality {
+        category: Prioritization,
+        quality: Quality,
+    },
+    //Dollars taken off the production cost of every product in the track
+    CostReduction(i32),
+    //Multiplies how long the track's products keep before spoiling
+    ShelfLife(f32),
+}
+
+pub struct ResearchNode {
+    pub id: NodeId,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub track: ResearchTrack,
+    //R&D points spent to unlock the node
+    pub cost: i32,
+    pub prerequisites: &'static [NodeId],
+    pub effect: ResearchEffect,
+}
+
+///Stable name of a node in `TREE`. Saves refer to nodes by these names,
+/// so the tree can be reordered without breaking them.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NodeId {
+    //Food
+    Preservation,
+    Salting,
+    BetterRecipes,
+    FineCuisine,
+    BulkCooking,
+    //Fighter
+    BetterForging,
+    TemperedBlades,
+    MasterSmithing,
+    EfficientForges,
+    //Cleric
+    BlessedCloth,
+    Consecration,
+    HolyRelics,
+    TempleWorkshops,
+    //Mage
+    WardingRunes,
+    Attunement,
+    Enchantment,
+    CheapReagents,
+}
+
+impl NodeId {
+    pub fn get(&self) -> &'static ResearchNode {
+        TREE.iter()
+            .find(|node| node.id == *self)
+            .expect("every NodeId has a node in TREE")
+    }
+
+    //In tree order
+    pub fn all() -> impl Iterator<Item = NodeId> {
+        TREE.iter().map(|node| node.id)
+    }
+}
+
+pub const TREE: [ResearchNode; 17] = [
+    //Food
+    ResearchNode {
+        id: NodeId::Preservation,
+        name: "Preservation",
+        description: "Food keeps half again as long before spoiling.",
+        track: ResearchTrack::Food,
+        cost: 2_000,
+        prerequisites: &[],
+        effect: ResearchEffect::ShelfLife(1.5),
+    },
+    ResearchNode {
+        id: NodeId::Salting,
+        name: "Salting",
+        description: "Food keeps half again as long before spoiling.",
+        track: ResearchTrack::Food,
+        cost: 5_000,
+        prerequisites: &[NodeId::Preservation],
+        effect: ResearchEffect::ShelfLife(1.5),
+    },
+    ResearchNode {
+        id: NodeId::BetterRecipes,
+        name: "Better Recipes",
+        description: "Good quality food.",
+        track: ResearchTrack::Food,
+        cost: 3_000,
+        prerequisites: &[],
+        effect: ResearchEffect::Quality {
+            category: Prioritization::Food,
+            quality: Quality::Good,
+        },
+    },
+    ResearchNode {
+        id: NodeId::FineCuisine,
+        name: "Fine Cuisine",
+        description: "Exceptional quality food.",
+        track: ResearchTrack::Food,
+        cost: 8_000,
+        prerequisites: &[NodeId::BetterRecipes],
+        effect: ResearchEffect::Quality {
+            category: Prioritization::Food,
+            quality: Quality::Exceptional,
+        },
+    },
+    ResearchNode {
+        id: NodeId::BulkCooking,
+        name: "Bulk Cooking",
+        description: "Food costs $5 less to produce.",
+        track: ResearchTrack::Food,
+        cost: 4_000,
+        prerequisites: &[NodeId::BetterRecipes],
+        effect: ResearchEffect::CostReduction(5),
+    },
+    //Fighter
+    ResearchNode {
+        id: NodeId::BetterForging,
+        name: "Better Forging",
+        description: "Good quality fighter armor.",
+        track: ResearchTrack::Fighter,
+        cost: 3_000,
+        prerequisites: &[],
+        effect: ResearchEffect::Quality {
+            category: Prioritization::Armor,
+            quality: Quality::Good,
+        },
+    },
+    ResearchNode {
+        id: NodeId::TemperedBlades,
+        name: "Tempered Blades",
+        description: "Good quality fighter weapons.",
+        track: ResearchTrack::Fighter,
+        cost: 3_000,
+        prerequisites: &[],
+        effect: ResearchEffect::Quality {
+            category: Prioritization::Weapon,
+            quality: Quality::Good,
+        },
+    },
+    ResearchNode {
+        id: NodeId::MasterSmithing,
+        name: "Master Smithing",
+        description: "Exceptional quality fighter armor.",
+        track: ResearchTrack::Fighter,
+        cost: 10_000,
+        prerequisites: &[NodeId::BetterForging, NodeId::TemperedBlades],
+        effect: ResearchEffect::Quality {
+            category: Prioritization::Armor,
+            quality: Quality::Exceptional,
+        },
+    },
+    ResearchNode {
+        id: NodeId::EfficientForges,
+        name: "Efficient Forges",
+        description: "Fighter gear costs $5 less to produce.",
+        track: ResearchTrack::Fighter,
+        cost: 5_000,
+        prerequisites: &[NodeId::BetterForging],
+        effect: ResearchEffect::CostReduction(5),
+    },
+    //Cleric
+    ResearchNode {
+        id: NodeId::BlessedCloth,
+        name: "Blessed Cloth",
+        description: "Good quality cleric armor.",
+        track: ResearchTrack::Cleric,
+        cost: 3_000,
+        prerequisites: &[],
+        effect: ResearchEffect::Quality {
+            category: Prioritization::Armor,
+            quality: Quality::Good,
+        },
+    },
+    ResearchNode {
+        id: NodeId::Consecration,
+        name: "Consecration",
+        description: "Good quality cleric weapons.",
+        track: ResearchTrack::Cleric,
+        cost: 3_000,
+        prerequisites: &[],
+        effect: ResearchEffect::Quality {
+            category: Prioritization::Weapon,
+            quality: Quality::Good,
+        },
+    },
+    ResearchNode {
+        id: NodeId::HolyRelics,
+        name: "Holy Relics",
+        description: "Exceptional quality cleric weapons.",
+        track: ResearchTrack::Cleric,
+        cost: 10_000,
+        prerequisites: &[NodeId::BlessedCloth, NodeId::Consecration],
+        effect: ResearchEffect::Quality {
+            category: Prioritization::Weapon,
+            quality: Quality::Exceptional,
+        },
+    },
+    ResearchNode {
+        id: NodeId::TempleWorkshops,
+        name: "Temple Workshops",
+        description: "Cleric gear costs $5 less to produce.",
+        track: ResearchTrack::Cleric,
+        cost: 5_000,
+        prerequisites: &[NodeId::Consecration],
+        effect: ResearchEffect::CostReduction(5),
+    },
+    //Mage
+    ResearchNode {
+        id: NodeId::WardingRunes,
+        name: "Warding Runes",
+        description: "Good quality mage armor.",
+        track: ResearchTrack::Mage,
+        cost: 4_000,
+        prerequisites: &[],
+        effect: ResearchEffect::Quality {
+            category: Prioritization::Armor,
+            quality: Quality::Good,
+        },
+    },
+    ResearchNode {
+        id: NodeId::Attunement,
+        name: "Attunement",
+        description: "Good quality mage weapons.",
+        track: ResearchTrack::Mage,
+        cost: 4_000,
+        prerequisites: &[],
+        effect: ResearchEffect::Quality {
+            category: Prioritization::Weapon,
+            quality: Quality::Good,
+        },
+    },
+    ResearchNode {
+        id: NodeId::Enchantment,
+        name: "Enchantment",
+        description: "Exceptional quality mage weapons.",
+        track: ResearchTrack::Mage,
+        cost: 12_000,
+        prerequisites: &[NodeId::Attunement, NodeId::WardingRunes],
+        effect: ResearchEffect::Quality {
+            category: Prioritization::Weapon,
+            quality: Quality::Exceptional,
+        },
+    },
+    ResearchNode {
+        id: NodeId::CheapReagents,
+        name: "Cheap Reagents",
+        description: "Mage gear costs $5 less to produce.",
+        track: ResearchTrack::Mage,
+        cost: 6_000,
+        prerequisites: &[NodeId::Attunement],
+        effect: ResearchEffect::CostReduction(5),
+    },
+];
+
+///R&D points earned per track and the nodes they have been spent on.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Research {
+    //Indexed by ResearchTrack
+    points: [i32; 4],
+    unlocked: Vec<NodeId>,
+}
+
+impl Research {
+    pub fn add_points(&mut self, track: ResearchTrack, points: i32) {
+        self.points[track as usize] += points;
+    }
+
+    pub fn points(&self, track: ResearchTrack) -> i32 {
+        self.points[track as usize]
+    }
+
+    pub fn is_unlocked(&self, node: NodeId) -> bool {
+        self.unlocked.contains(&node)
+    }
+
+    pub fn prerequisites_met(&self, node: NodeId) -> bool {
+        node.get()
+            .prerequisites
+            .iter()
+            .all(|&prerequisite| self.is_unlocked(prerequisite))
+    }
+
+    pub fn unlock(&mut self, node: NodeId) -> Result<(), String> {
+        let def = node.get();
+
+        if self.is_unlocked(node) {
+            return Err(format!("[{}] is already researched.", def.name));
+        }
+
+        if !self.prerequisites_met(node) {
+            return Err(format!("[{}] needs more research first.", def.name));
+        }
+
+        let points = &mut self.points[def.track as usize];
+        if *points < def.cost {
+            return Err(format!("[{}] needs {} R&D points.", def.name, def.cost));
+        }
+
+        *points -= def.cost;
+        self.unlocked.push(node);
+        Ok(())
+    }
+
+    pub fn unlocked(&self) -> impl Iterator<Item = &'static ResearchNode> + '_ {
+        self.unlocked.iter().map(|node| node.get())
+    }
+
+    //Highest quality researched for a product, Basic if none
+    pub fn quality(&self, track: ResearchTrack, category: Prioritization) -> Quality {
+        self.unlocked()
+            .filter(|node| node.track == track)
+            .filter_map(|node| match node.effect {
+                ResearchEffect::Quality {
+                    category: c,
+                    quality,
+                } if c == category => Some(quality),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(Quality::Basic)
+    }
+
+    pub fn cost_reduction(&self, track: ResearchTrack) -> i32 {
+        self.unlocked()
+            .filter(|node| node.track == track)
+            .filter_map(|node| match node.effect {
+                ResearchEffect::CostReduction(amount) => Some(amount),
+                _ => None,
+            })
+            .sum()
+    }
+
+    pub fn shelf_life_multiplier(&self, track: ResearchTrack) -> f32 {
+        self.unlocked()
+            .filter(|node| node.track == track)
+            .filter_map(|node| match node.effect {
+                ResearchEffect::ShelfLife(multiplier) => Some(multiplier),
+                _ => None,
+            })
+            .product()
+    }
+
+    pub fn can_unlock(&self, node: NodeId) -> bool {
+        !self.is_unlocked(node)
+            && self.prerequisites_met(node)
+            && self.points(node.get().track) >= node.get().cost
+    }
+
+    //Returns the node the player chose to research, if any
+    pub fn show(&self, ui: &mut Ui) -> Option<NodeId> {
+        let mut chosen = None;
+
+        for track in ResearchTrack::ALL {
+            if track != ResearchTrack::Food {
+                ui.separator();
+            }
+
+            ui.strong(format!("{} ({} points)", track.name(), self.points(track)));
+
+            Grid::new(track.name()).num_columns(3).show(ui, |ui| {
+                for node in NodeId::all().filter(|node| node.get().track == track) {
+                    let def = node.get();
+
+                    let prerequisites: Vec<&str> = def
+                        .prerequisites
+                        .iter()
+                        .map(|prerequisite| prerequisite.get().name)
+                        .collect();
+                    let mut tooltip = String::from(def.description);
+                    if !prerequisites.is_empty() {
+                        tooltip += &format!("\nRequires: {}", prerequisites.join(", "));
+                    }
+
+                    ui.label(def.name).on_hover_text(tooltip);
+                    ui.label(format!("{} points", def.cost));
+
+                    if self.is_unlocked(node) {
+                        ui.label("Researched");
+                    } else if !self.prerequisites_met(node) {
+                        ui.weak("Locked");
+                    } else if ui
+                        .add_enabled(self.can_unlock(node), Button::new("Research"))
+                        .clicked()
+                    {
+                        chosen = Some(node);
+                    }
+                    ui.end_row();
+                }
+            });
+        }
+
+        chosen
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unlock_needs_prerequisites_and_points() {
+        let mut research = Research::default();
+        let food = ResearchTrack::Food;
+        research.add_points(food, 7_000);
+
+        assert!(research.unlock(NodeId::Salting).is_err());
+        assert_eq!(research.points(food), 7_000);
+
+        research.unlock(NodeId::Preservation).unwrap();
+        assert_eq!(research.points(food), 5_000);
+        assert!(research.unlock(NodeId::Preservation).is_err());
+
+        research.unlock(NodeId::Salting).unwrap();
+        assert_eq!(research.points(food), 0);
+        assert_eq!(research.shelf_life_multiplier(food), 2.25);
+
+        //Points only count towards their own track
+        research.add_points(ResearchTrack::Mage, 10_000);
+        assert!(!research.can_unlock(NodeId::BetterRecipes));
+        assert!(research.unlock(NodeId::BetterRecipes).is_err());
+    }
+
+    #[test]
+    fn every_node_is_in_the_tree_once() {
+        for node in NodeId::all() {
+            assert_eq!(TREE.iter().filter(|def| def.id == node).count(), 1);
+            assert!(node
+                .get()
+                .prerequisites
+                .iter()
+                .all(|prerequisite| prerequisite.get().track == node.get().track));
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+
+use crate::simulation::Simulation;
+
+//Bump whenever the saved state changes shape. Saves from any other
+// version are rejected rather than migrated.
+pub const SAVE_VERSION: u32 = 1;
+
+#[cfg(not(target_arch = "wasm32"))]
+const SAVE_PATH: &str = "savegame.json";
+
+#[cfg(target_arch = "wasm32")]
+const SAVE_KEY: &str = "dungeon_and_finances_save";
+
+#[derive(Serialize)]
+struct SaveFileRef<'a> {
+    version: u32,
+    simulation: &'a Simulation,
+}
+
+#[derive(Deserialize)]
+struct SaveFile {
+    simulation: Simulation,
+}
+
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+pub fn serialize(simulation: &Simulation) -> Result<String, String> {
+    let file = SaveFileRef {
+        version: SAVE_VERSION,
+        simulation,
+    };
+    serde_json::to_string(&file).map_err(|e| e.to_string())
+}
+
+pub fn deserialize(data: &str) -> Result<Simulation, String> {
+    //Check the version first so old saves give a clear error instead of a parse error
+    let header: SaveHeader = serde_json::from_str(data).map_err(|e| e.to_string())?;
+    if header.version != SAVE_VERSION {
+        return Err(format!(
+            "Save version {} is not supported (expected {}).",
+            header.version, SAVE_VERSION
+        ));
+    }
+
+    let file: SaveFile = serde_json::from_str(data).map_err(|e| e.to_string())?;
+    file.simulation.business().catalog().validate()?;
+    Ok(file.simulation)
+}
+
+pub fn save(simulation: &Simulation) -> Result<(), String> {
+    write(&serialize(simulation)?)
+}
+
+pub fn load() -> Result<Simulation, String> {
+    deserialize(&read()?)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write(data: &str) -> Result<(), String> {
+    std::fs::write(SAVE_PATH, data).map_err(|e| e.to_string())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read() -> Result<String, String> {
+    std::fs::read_to_string(SAVE_PATH).map_err(|e| e.to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Result<web_sys::Storage, String> {
+    web_sys::window()
+        .ok_or("No browser window available.")?
+        .local_storage()
+        .map_err(|_| "Local storage is not accessible.")?
+        .ok_or_else(|| String::from("Local storage is not available."))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write(data: &str) -> Result<(), String> {
+    local_storage()?
+        .set_item(SAVE_KEY, data)
+        .map_err(|_| String::from("Failed to write to local storage."))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read() -> Result<String, String> {
+    local_storage()?
+        .get_item(SAVE_KEY)
+        .map_err(|_| "Failed to read from local storage.")?
+        .ok_or_else(|| String::from("No saved game found."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::Catalog;
+    use crate::simulation::play;
+
+    #[test]
+    fn loaded_save_continues_the_same_run() {
+        let mut unsaved = Simulation::new(10_000, 7, Catalog::default());
+        let mut original = Simulation::new(10_000, 7, Catalog::default());
+        play(&mut unsaved, 2 * Simulation::TICKS_PER_DAY);
+        play(&mut original, Simulation::TICKS_PER_DAY);
+
+        let mut loaded = deserialize(&serialize(&original).unwrap()).unwrap();
+
+        play(&mut original, Simulation::TICKS_PER_DAY);
+        play(&mut loaded, Simulation::TICKS_PER_DAY);
+
+        assert_eq!(original.business().funds(), loaded.business().funds());
+        assert_eq!(serialize(&original), serialize(&loaded));
+        //Saving must not change the run either
+        assert_eq!(serialize(&original), serialize(&unsaved));
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let simulation = Simulation::new(10_000, 7, Catalog::default());
+
+        let mut file: serde_json::Value =
+            serde_json::from_str(&serialize(&simulation).unwrap()).unwrap();
+        file["version"] = (SAVE_VERSION - 1).into();
+
+        let error = deserialize(&file.to_string()).err().unwrap();
+        assert!(error.contains("not supported"));
+    }
+
+    #[test]
+    fn rejects_invalid_catalogs() {
+        let simulation = Simulation::new(10_000, 7, Catalog::default());
+
+        let mut file: serde_json::Value =
+            serde_json::from_str(&serialize(&simulation).unwrap()).unwrap();
+        file["simulation"]["business"]["catalog"][0]["base_cost"] = 0.into();
+
+        let error = deserialize(&file.to_string()).err().unwrap();
+        assert!(error.contains("must cost more than $0"));
+    }
+}
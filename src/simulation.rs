@@ -0,0 +1,855 @@
+use core::f32;
+use std::collections::VecDeque;
+
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::bank::LoanDraft;
+use crate::business::{Business, Quality};
+use crate::calendar::Date;
+use crate::catalog::{Catalog, ProductId};
+use crate::client::{BasketItem, ClassType, Client, Prioritization};
+use crate::events::{EventLog, GameEvent};
+use crate::haggle::{Haggle, HaggleReply};
+use crate::history::History;
+use crate::market::Market;
+use crate::marketing::CampaignDraft;
+use crate::materials::Material;
+use crate::reputation::{Reputation, Satisfaction};
+use crate::research::NodeId;
+
+#[derive(Clone, Copy)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn starting_funds(&self) -> i32 {
+        match self {
+            Difficulty::Easy => 200_000,
+            Difficulty::Medium => 10_000,
+            Difficulty::Hard => 5_000,
+        }
+    }
+}
+
+//Progress through the basket of the client at the counter
+#[derive(Serialize, Deserialize)]
+struct Visit {
+    class: ClassType,
+    price_factor: f32,
+    quality_factor: f32,
+    //Items not yet shopped for, most wanted first
+    basket: VecDeque<BasketItem>,
+    //Budget left unspent on earlier items
+    carried: i32,
+    haggle: Option<Haggle>,
+    //How the client felt about each item shopped for so far
+    ratings: Vec<Satisfaction>,
+}
+
+//What happened when a client was offered a product
+enum OfferOutcome {
+    //Not for sale, out of stock or not wanted, so the client looks elsewhere
+    Unavailable(Satisfaction),
+    Spent(i32, Satisfaction),
+    //Over the client's budget, so they only buy it if the price comes down
+    Haggle(Haggle),
+}
+
+///Simulation owns the shop's economy: the business, the line of clients
+/// and the spawn probabilities. It knows nothing about graphics or audio,
+/// so it can be driven by the game, by tools or by tests through `step`.
+/// Every random decision goes through one seeded RNG, so the same seed and
+/// the same player inputs always produce the same run.
+#[derive(Serialize, Deserialize)]
+pub struct Simulation {
+    seed: u64,
+    //Saved mid-stream, so a loaded run continues exactly where it left off
+    rng: ChaCha12Rng,
+
+    business: Business,
+    market: Market,
+    //Everyone in the shop in arrival order, including clients walking out
+    clients: VecDeque<Client>,
+    visit: Option<Visit>,
+    log: EventLog,
+    history: History,
+    reputation: Reputation,
+
+    //Steps since the run started. Game time is derived from this so it
+    // never drifts the way summing TICK would.
+    ticks: u64,
+    //When the client at the counter was served
+    visit_start: f32,
+
+    //Clients per second walking into the shop on average
+    arrival_rate: f32,
+    //Seconds until the next client walks in
+    next_arrival: f32,
+
+    production_time: f32,
+
+    mage_probability: f32,
+    fighter_probability: f32,
+
+    food_probability: f32,
+    weapon_probability: f32,
+}
+
+impl Simulation {
+    pub const TICKS_PER_SECOND: u64 = 60;
+
+    //Seconds of game time covered by a single step
+    pub const TICK: f32 = 1.0 / Self::TICKS_PER_SECOND as f32;
+
+    //Seconds of game time in one in-game day
+    pub const DAY_LENGTH: f32 = 60.0;
+
+    pub const TICKS_PER_DAY: u64 = Self::DAY_LENGTH as u64 * Self::TICKS_PER_SECOND;
+
+    //Seconds between each production run
+    pub const PRODUCTION_INTERVAL: f32 = 5.0;
+
+    //Clients stop walking in while this many are waiting, counting the
+    // one at the counter
+    pub const MAX_QUEUE: usize = 5;
+
+    //Range of seconds a client waits in line before giving up
+    const MIN_PATIENCE: f32 = 15.0;
+    const MAX_PATIENCE: f32 = 30.0;
+
+    //How a client's income is split over the items in their basket
+    const BUDGET_WEIGHTS: [i32; 3] = [3, 2, 1];
+
+    pub fn new(initial_funds: i32, seed: u64, catalog: Catalog) -> Self {
+        let business = Business::new(initial_funds, catalog);
+
+        Self {
+            seed,
+            rng: ChaCha12Rng::seed_from_u64(seed),
+
+            market: Market::new(&business),
+            business,
+            clients: VecDeque::new(),
+            visit: None,
+            log: EventLog::default(),
+            history: History::default(),
+            reputation: Reputation::default(),
+
+            ticks: 0,
+            visit_start: 0.0,
+
+            arrival_rate: 0.25,
+            next_arrival: 0.0,
+
+            production_time: 0.0,
+
+            mage_probability: 0.3,
+            fighter_probability: 0.3,
+            food_probability: 0.3,
+            weapon_probability: 0.3,
+        }
+    }
+
+    ///Advances the economy by one `Simulation::TICK`. Steps are fixed so
+    /// runs do not depend on the frame rate.
+    pub fn step(&mut self) {
+        let dt = Self::TICK;
+
+        //The run is over once the shop goes bankrupt
+        if self.is_bankrupt() {
+            return;
+        }
+
+        let day = self.day();
+        self.ticks += 1;
+        self.log.set_time(self.elapsed());
+        self.business.set_time(self.elapsed());
+        self.business.finish_production();
+        for campaign in self.business.expire_campaigns() {
+            self.log.push(GameEvent::CampaignEnded {
+                target: campaign.target,
+            });
+        }
+        self.business.spoil(&mut self.log);
+
+        if self.day() > day {
+            self.end_day(day);
+        }
+
+        self.production_time += dt;
+
+        if self.production_time >= Self::PRODUCTION_INTERVAL {
+            self.production_time -= Self::PRODUCTION_INTERVAL;
+            self.business.update_quantities(&mut self.log);
+        }
+
+        self.next_arrival -= dt;
+        if self.next_arrival <= 0.0 {
+            let client = self.create_client();
+            if self.waiting().count() < Self::MAX_QUEUE {
+                self.clients.push_back(client);
+            } else {
+                self.turn_away(&client);
+            }
+            self.next_arrival += self.arrival_interval();
+        }
+
+        self.update_clients(dt);
+
+        self.history
+            .record(self.ticks, self.elapsed(), &self.business);
+    }
+
+    //Runs once as `day` turns into the next one
+    fn end_day(&mut self, day: u32) {
+        let profit = self
+            .business
+            .ledger()
+            .day_statement(day, Self::DAY_LENGTH)
+            .net_profit();
+        self.log.push(GameEvent::DayEnded { day, profit });
+        self.business.close_books(day);
+
+        let today = self.date();
+        if today.season() != Date::new(day).season() {
+            self.log.push(GameEvent::SeasonStarted {
+                season: today.season(),
+            });
+        }
+
+        self.business.pay_holding_costs();
+        self.business.pay_bills(today.day(), &mut self.log);
+        self.business.service_debt(&mut self.log);
+        self.market.reprice(&self.business, &mut self.rng);
+        self.business.update_material_prices(&mut self.rng);
+    }
+
+    //Exponentially distributed, so arrivals follow a Poisson process
+    fn arrival_interval(&mut self) -> f32 {
+        let rng: f32 = self.rng.gen_range(0.0..1.0);
+        -(1.0 - rng).ln() / (self.arrival_rate * self.reputation.arrival_multiplier())
+    }
+
+    //Clients that have not started walking out, front of the line first
+    fn waiting(&self) -> impl Iterator<Item = &Client> {
+        self.clients.iter().filter(|client| !client.exit())
+    }
+
+    fn update_clients(&mut self, dt: f32) {
+        let mut place = 0;
+        let mut serve = false;
+
+        for client in self.clients.iter_mut() {
+            client.walk(dt, place);
+
+            if client.exit() {
+                continue;
+            }
+
+            if !client.is_purchase_complete() {
+                if place == 0 && client.center() {
+                    serve = self.visit.is_none();
+                } else if client.wait(dt) {
+                    client.give_up();
+                    self.reputation
+                        .record_visit(*client.get_class_type(), &[Satisfaction::GaveUp]);
+                    self.log.push(GameEvent::LostSale {
+                        class: *client.get_class_type(),
+                        product: client.want(self.business.catalog()),
+                    });
+                    continue;
+                }
+            }
+
+            place += 1;
+        }
+
+        self.clients.retain(|client| !client.is_complete());
+
+        if serve {
+            self.serve_client();
+        }
+    }
+
+    fn create_client(&mut self) -> Client {
+        //Class-targeted campaigns and a good name with a class draw more of
+        // that class into the shop
+        let marketing = self.business.marketing();
+        let weight = |class| {
+            marketing.spawn_weight(class, self.elapsed()) * self.reputation.class_weight(class)
+        };
+        let mage_weight = self.mage_probability * weight(ClassType::Mage);
+        let fighter_weight = self.fighter_probability * weight(ClassType::Fighter);
+        let cleric_weight =
+            (1.0 - self.mage_probability - self.fighter_probability) * weight(ClassType::Cleric);
+
+        let rng = self.rng.gen_range(0.0..1.0) * (mage_weight + fighter_weight + cleric_weight);
+
+        let class = if rng < mage_weight {
+            ClassType::Mage
+        } else if rng < mage_weight + fighter_weight {
+            ClassType::Fighter
+        } else {
+            ClassType::Cleric
+        };
+
+        let rng = self.rng.gen_range(0.0..1.0);
+
+        let priority = if rng < self.food_probability {
+            Prioritization::Food
+        } else if rng < self.food_probability + self.weapon_probability {
+            Prioritization::Weapon
+        } else {
+            Prioritization::Armor
+        };
+
+        let income = self.rng.gen_range(50..=100);
+        let basket = self.create_basket(priority, income);
+        let patience = self.rng.gen_range(Self::MIN_PATIENCE..=Self::MAX_PATIENCE);
+
+        Client::new(class, 0.5, 0.4, basket, income, patience)
+    }
+
+    //A client who walks in to a full line leaves at once, unhappy
+    fn turn_away(&mut self, client: &Client) {
+        let class = *client.get_class_type();
+        self.reputation.record_visit(class, &[Satisfaction::GaveUp]);
+        self.log.push(GameEvent::TurnedAway {
+            class,
+            product: client.want(self.business.catalog()),
+        });
+    }
+
+    //The main priority comes first, followed by up to two other categories
+    fn create_basket(&mut self, priority: Prioritization, income: i32) -> Vec<BasketItem> {
+        let mut others: Vec<Prioritization> = Prioritization::ALL
+            .into_iter()
+            .filter(|&category| category != priority)
+            .collect();
+        others.shuffle(&mut self.rng);
+        others.truncate(self.rng.gen_range(0..=others.len()));
+
+        let categories: Vec<Prioritization> = std::iter::once(priority).chain(others).collect();
+
+        //Items higher up the basket get a bigger share of the income
+        let weights = &Self::BUDGET_WEIGHTS[..categories.len()];
+        let total_weight: i32 = weights.iter().sum();
+
+        let mut remaining = income;
+        let mut basket: Vec<BasketItem> = categories
+            .into_iter()
+            .zip(weights)
+            .map(|(category, weight)| {
+                let budget = income * weight / total_weight;
+                remaining -= budget;
+                BasketItem { category, budget }
+            })
+            .collect();
+
+        //Rounding leftovers go to the most wanted item
+        basket[0].budget += remaining;
+        basket
+    }
+
+    //Runs once the client reaches the counter
+    fn serve_client(&mut self) {
+        let Some(client) = self.clients.iter().find(|client| !client.exit()) else {
+            return;
+        };
+        self.visit_start = self.elapsed();
+
+        self.business
+            .update_demand(client.price_factor(), client.quality_factor());
+
+        let class = *client.get_class_type();
+        self.log.push(GameEvent::ClientArrived { class });
+
+        self.visit = Some(Visit {
+            class,
+            price_factor: client.price_factor(),
+            quality_factor: client.quality_factor(),
+            basket: client.basket().iter().copied().collect(),
+            carried: 0,
+            haggle: None,
+            ratings: Vec::new(),
+        });
+        self.continue_visit();
+    }
+
+    //Shops for the rest of the basket until it is empty or a haggle starts
+    fn continue_visit(&mut self) {
+        let Some(mut visit) = self.visit.take() else {
+            return;
+        };
+
+        while visit.haggle.is_none() {
+            let Some(item) = visit.basket.pop_front() else {
+                self.reputation.record_visit(visit.class, &visit.ratings);
+                if let Some(client) = self.clients.iter_mut().find(|client| !client.exit()) {
+                    client.complete_purchase();
+                }
+                return;
+            };
+
+            let budget = item.budget + visit.carried;
+            match self.shop_for(
+                item.category,
+                visit.class,
+                budget,
+                visit.price_factor,
+                visit.quality_factor,
+            ) {
+                OfferOutcome::Haggle(haggle) => visit.haggle = Some(haggle),
+                OfferOutcome::Spent(spent, satisfaction) => {
+                    visit.carried = budget - spent;
+                    visit.ratings.push(satisfaction);
+                }
+                OfferOutcome::Unavailable(satisfaction) => {
+                    visit.carried = budget;
+                    visit.ratings.push(satisfaction);
+                }
+            }
+        }
+
+        self.visit = Some(visit);
+    }
+
+    //Ends the current haggle and moves on to the rest of the basket
+    fn close_haggle(&mut self, price: Option<i32>) {
+        let Some(haggle) = self.visit.as_mut().and_then(|visit| visit.haggle.take()) else {
+            return;
+        };
+
+        let product = haggle.product();
+        let (spent, satisfaction) = match price {
+            //The shelf may have changed while the client was haggling
+            Some(_) if self.business.get_price(product) == 0 => {
+                self.log.push(GameEvent::NotForSale { product });
+                (0, Satisfaction::NotSold)
+            }
+            Some(_) if self.business.get_quantity(product) <= 0 => {
+                self.log.push(GameEvent::StockOut { product });
+                (0, Satisfaction::OutOfStock)
+            }
+            Some(price) => (
+                self.business.purchase_at(product, 1, price, &mut self.log),
+                Satisfaction::Bought(self.business.get_quality(product)),
+            ),
+            None => {
+                self.log.push(GameEvent::HaggleFailed { product });
+                (0, Satisfaction::TooExpensive)
+            }
+        };
+
+        if spent > 0 {
+            self.market.record(product, None);
+        }
+
+        if let Some(visit) = &mut self.visit {
+            visit.carried = haggle.budget() - spent;
+            visit.ratings.push(satisfaction);
+        }
+        self.continue_visit();
+    }
+
+    ///Sells the haggled unit at the client's current offer.
+    pub fn accept_offer(&mut self) {
+        let offer = self.haggle().map(|haggle| haggle.offer());
+        if offer.is_some() {
+            self.close_haggle(offer);
+        }
+    }
+
+    pub fn reject_offer(&mut self) {
+        if self.haggle().is_some() {
+            self.close_haggle(None);
+        }
+    }
+
+    pub fn counter_offer(&mut self, price: i32) {
+        let Some(haggle) = self.visit.as_mut().and_then(|visit| visit.haggle.as_mut()) else {
+            return;
+        };
+
+        let product = haggle.product();
+        match haggle.counter(price) {
+            HaggleReply::Accept(price) => self.close_haggle(Some(price)),
+            HaggleReply::Offer(offer) => {
+                self.log.push(GameEvent::ClientOffers { product, offer });
+            }
+            HaggleReply::WalkAway => self.close_haggle(None),
+        }
+    }
+
+    pub fn haggle(&self) -> Option<&Haggle> {
+        self.visit.as_ref().and_then(|visit| visit.haggle.as_ref())
+    }
+
+    //Tries the product made for the client's class first, then substitutes
+    // from the same category. The client only haggles when nothing in the
+    // category sells within their budget.
+    fn shop_for(
+        &mut self,
+        category: Prioritization,
+        class: ClassType,
+        budget: i32,
+        price_factor: f32,
+        quality_factor: f32,
+    ) -> OfferOutcome {
+        let candidates = self.business.catalog().candidates(&category, &class);
+
+        let Some(&wanted) = candidates.first() else {
+            self.log.push(GameEvent::NothingToOffer);
+            return OfferOutcome::Unavailable(Satisfaction::NotSold);
+        };
+
+        //The client remembers why the product they wanted was not bought
+        let mut reason = None;
+        //First product that was too expensive, kept to haggle over
+        let mut haggle = None;
+
+        self.log.push(GameEvent::ClientWants { product: wanted });
+
+        for product in candidates {
+            if product != wanted {
+                self.log.push(GameEvent::Substitution { wanted, product });
+            }
+
+            match self.offer(product, budget, price_factor, quality_factor) {
+                OfferOutcome::Unavailable(satisfaction) => {
+                    reason.get_or_insert(satisfaction);
+                }
+                OfferOutcome::Haggle(overpriced) => {
+                    haggle.get_or_insert(overpriced);
+                }
+                outcome => return outcome,
+            }
+        }
+
+        if let Some(haggle) = haggle {
+            self.log.push(GameEvent::ClientOffers {
+                product: haggle.product(),
+                offer: haggle.offer(),
+            });
+            return OfferOutcome::Haggle(haggle);
+        }
+
+        //Nothing we have will do, so the client tries the rivals instead
+        if let Some(outcome) = self.visit_rivals(wanted, None, budget, price_factor, quality_factor)
+        {
+            return outcome;
+        }
+
+        OfferOutcome::Unavailable(reason.unwrap_or(Satisfaction::NotSold))
+    }
+
+    //Lets the client compare our deal with the rivals', returning what they
+    // spent when a rival wins. ours: None when we cannot sell `want` to them
+    fn visit_rivals(
+        &mut self,
+        want: ProductId,
+        ours: Option<(i32, Quality)>,
+        budget: i32,
+        price_factor: f32,
+        quality_factor: f32,
+    ) -> Option<OfferOutcome> {
+        let index = self.market.choose(
+            want,
+            ours,
+            budget,
+            price_factor,
+            quality_factor,
+            &mut self.rng,
+        )?;
+
+        let rival = &self.market.rivals()[index];
+        let spent = rival.price(want);
+        self.log.push(GameEvent::BoughtFromRival {
+            product: want,
+            rival: rival.name.clone(),
+        });
+        self.market.record(want, Some(index));
+        Some(OfferOutcome::Spent(spent, Satisfaction::WentToRival))
+    }
+
+    fn offer(
+        &mut self,
+        want: ProductId,
+        budget: i32,
+        price_factor: f32,
+        quality_factor: f32,
+    ) -> OfferOutcome {
+        let price = self.business.get_price(want);
+        let demand = self.business.get_demand(want);
+
+        let stock = self.business.get_quantity(want);
+
+        //When we can make the sale, the client still compares our deal with
+        // the rivals' first. Otherwise substitutes and haggling come first.
+        if price > 0 && stock > 0 && price <= budget {
+            let ours = Some((price, self.business.get_quality(want)));
+            if let Some(outcome) =
+                self.visit_rivals(want, ours, budget, price_factor, quality_factor)
+            {
+                return outcome;
+            }
+        }
+
+        if price == 0 {
+            self.log.push(GameEvent::NotForSale { product: want });
+            return OfferOutcome::Unavailable(Satisfaction::NotSold);
+        }
+
+        //If stock is negative, there's a problem.
+        if stock <= 0 {
+            self.log.push(GameEvent::StockOut { product: want });
+            return OfferOutcome::Unavailable(Satisfaction::OutOfStock);
+        }
+
+        if price > budget {
+            self.log.push(GameEvent::PriceTooHigh { product: want });
+            return OfferOutcome::Haggle(Haggle::open(want, price, budget, demand, price_factor));
+        }
+
+        let quantity = (demand as f32 / 2.0) * (budget as f32 / price as f32);
+        let quantity_multiplier = 1.0 + self.rng.gen_range(-0.5..=0.75);
+        let final_quantity = quantity * quantity_multiplier;
+        let final_quantity = final_quantity as i32;
+        let final_quantity = final_quantity.min(demand).min(budget / price);
+
+        if final_quantity == 0 {
+            self.log.push(GameEvent::NoDemand { product: want });
+            return OfferOutcome::Unavailable(Satisfaction::TooExpensive);
+        }
+
+        self.log.push(GameEvent::ClientSeeks {
+            product: want,
+            units: final_quantity,
+        });
+
+        if final_quantity > stock {
+            self.log.push(GameEvent::NotEnoughStock { product: want });
+        }
+
+        let e = f32::consts::E;
+        let purchase_probability =
+            demand as f32 / (demand as f32 + e.powi((price - budget).max(-5)));
+
+        let rng = self.rng.gen_range(0.0..=1.0);
+        if rng < purchase_probability {
+            let quality = self.business.get_quality(want);
+            let revenue = self.business.purchase(want, final_quantity, &mut self.log);
+            //Only an actual sale counts as winning the client over the rivals
+            if revenue > 0 {
+                self.market.record(want, None);
+            }
+            OfferOutcome::Spent(revenue, Satisfaction::Bought(quality))
+        } else {
+            self.log.push(GameEvent::SaleDeclined { product: want });
+            OfferOutcome::Spent(0, Satisfaction::Declined)
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    ///Restarts the random sequence from `seed`.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = ChaCha12Rng::seed_from_u64(seed);
+    }
+
+    ///Sends the client at the counter on their way out of the shop.
+    pub fn complete_order(&mut self) {
+        //Anything left in the basket goes unsold
+        if let Some(mut visit) = self.visit.take() {
+            let unsold = visit.basket.len() + usize::from(visit.haggle.is_some());
+            visit
+                .ratings
+                .extend(std::iter::repeat_n(Satisfaction::NotSold, unsold));
+            self.reputation.record_visit(visit.class, &visit.ratings);
+        }
+
+        if let Some(client) = self.clients.iter_mut().find(|client| !client.exit()) {
+            client.complete();
+        }
+    }
+
+    pub fn launch_campaign(&mut self, draft: &CampaignDraft) -> Result<(), String> {
+        let campaign = self.business.launch_campaign(draft)?;
+        self.log.push(GameEvent::CampaignLaunched {
+            target: campaign.target,
+        });
+        Ok(())
+    }
+
+    pub fn buy_material(&mut self, material: Material, units: i32) -> Result<(), String> {
+        let cost = self.business.buy_material(material, units)?;
+        self.log.push(GameEvent::MaterialsBought {
+            material,
+            units,
+            cost,
+        });
+        Ok(())
+    }
+
+    pub fn take_loan(&mut self, draft: &LoanDraft) -> Result<(), String> {
+        let loan = self.business.take_loan(draft)?;
+        self.log.push(GameEvent::LoanTaken {
+            amount: loan.amount,
+        });
+        Ok(())
+    }
+
+    pub fn is_bankrupt(&self) -> bool {
+        self.business.bank().is_bankrupt()
+    }
+
+    pub fn unlock_research(&mut self, node: NodeId) -> Result<(), String> {
+        self.business.unlock_research(node)?;
+        self.log.push(GameEvent::ResearchUnlocked { node });
+        Ok(())
+    }
+
+    pub fn business(&self) -> &Business {
+        &self.business
+    }
+
+    pub fn business_mut(&mut self) -> &mut Business {
+        &mut self.business
+    }
+
+    //The client at the front of the line
+    pub fn client(&self) -> Option<&Client> {
+        self.waiting().next()
+    }
+
+    pub fn clients(&self) -> impl DoubleEndedIterator<Item = &Client> {
+        self.clients.iter()
+    }
+
+    //Clients in line behind the one at the counter
+    pub fn queue_length(&self) -> usize {
+        self.waiting().count().saturating_sub(1)
+    }
+
+    pub fn log(&self) -> &EventLog {
+        &self.log
+    }
+
+    pub fn market(&self) -> &Market {
+        &self.market
+    }
+
+    pub fn reputation(&self) -> &Reputation {
+        &self.reputation
+    }
+
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    //Seconds of game time since the run started
+    pub fn elapsed(&self) -> f32 {
+        (self.ticks as f64 / Self::TICKS_PER_SECOND as f64) as f32
+    }
+
+    //Current in-game day, starting at 0
+    pub fn day(&self) -> u32 {
+        (self.ticks / Self::TICKS_PER_DAY) as u32
+    }
+
+    pub fn date(&self) -> Date {
+        Date::new(self.day())
+    }
+
+    pub fn visit_start(&self) -> f32 {
+        self.visit_start
+    }
+}
+
+//Plays the shop without a player: haggles are turned down and every
+// served client is sent on their way
+#[cfg(test)]
+pub(crate) fn play(simulation: &mut Simulation, ticks: u64) {
+    for _ in 0..ticks {
+        simulation.step();
+
+        if simulation.haggle().is_some() {
+            simulation.reject_offer();
+        }
+        if simulation
+            .client()
+            .is_some_and(|client| client.is_purchase_complete() && !client.exit())
+        {
+            simulation.complete_order();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_lines(simulation: &Simulation) -> Vec<String> {
+        let catalog = simulation.business().catalog();
+        simulation
+            .log()
+            .iter()
+            .map(|logged| format!("{:.3} {}", logged.time, logged.event.describe(catalog)))
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_same_run() {
+        let mut first = Simulation::new(10_000, 42, Catalog::default());
+        let mut second = Simulation::new(10_000, 42, Catalog::default());
+
+        play(&mut first, 2 * Simulation::TICKS_PER_DAY);
+        play(&mut second, 2 * Simulation::TICKS_PER_DAY);
+
+        assert_eq!(first.business().funds(), second.business().funds());
+        assert!(first.log().iter().count() > 0);
+        assert_eq!(log_lines(&first), log_lines(&second));
+    }
+
+    #[test]
+    fn baskets_split_the_whole_income() {
+        let mut simulation = Simulation::new(10_000, 3, Catalog::default());
+
+        for income in 50..=100 {
+            let basket = simulation.create_basket(Prioritization::Armor, income);
+
+            assert!((1..=3).contains(&basket.len()));
+            assert!(basket[0].category == Prioritization::Armor);
+            assert_eq!(basket.iter().map(|item| item.budget).sum::<i32>(), income);
+            //Most wanted first, so budgets never grow down the basket
+            assert!(basket
+                .windows(2)
+                .all(|pair| pair[0].budget >= pair[1].budget));
+            for (i, item) in basket.iter().enumerate() {
+                assert!(basket[i + 1..]
+                    .iter()
+                    .all(|other| other.category != item.category));
+            }
+        }
+    }
+
+    #[test]
+    fn day_counts_whole_ticks() {
+        let mut simulation = Simulation::new(10_000, 0, Catalog::default());
+
+        simulation.ticks = 365 * Simulation::TICKS_PER_DAY - 1;
+        assert_eq!(simulation.day(), 364);
+
+        simulation.ticks += 1;
+        assert_eq!(simulation.day(), 365);
+    }
+}
@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+///Storage space for the shop's stock. Every unit takes up the space given
+/// by its product in the catalog.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Warehouse {
+    capacity: i32,
+    //Number of upgrades bought
+    level: u32,
+}
+
+impl Default for Warehouse {
+    fn default() -> Self {
+        Self {
+            capacity: Self::BASE_CAPACITY,
+            level: 0,
+        }
+    }
+}
+
+impl Warehouse {
+    const BASE_CAPACITY: i32 = 500;
+
+    //Space added by each upgrade
+    pub const UPGRADE_CAPACITY: i32 = 500;
+
+    //Dollars charged per day for each unit of space in use
+    pub const HOLDING_COST: i32 = 1;
+
+    pub fn capacity(&self) -> i32 {
+        self.capacity
+    }
+
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    //Every upgrade costs more than the last
+    pub fn upgrade_cost(&self) -> i32 {
+        5_000 * (self.level as i32 + 1)
+    }
+
+    pub fn upgrade(&mut self) {
+        self.level += 1;
+        self.capacity += Self::UPGRADE_CAPACITY;
+    }
+}